@@ -27,6 +27,9 @@ pub mod pytypes;
 
 // re-export
 pub use self::pytypes::pybool::PyBool;
+pub use self::pytypes::pybuffer::{ElementType, PyBuffer};
+pub use self::pytypes::pybytes::PyBytes;
+pub use self::pytypes::pydatetime::{PyDate, PyDateTime, PyTime};
 pub use self::pytypes::pydict::PyDict;
 pub use self::pytypes::pylist::PyList;
 pub use self::pytypes::pystring::PyString;
@@ -3,7 +3,8 @@
 use libc::{c_char, size_t};
 
 use std::collections::HashMap;
-use std::convert::AsRef;
+use std::convert::{AsRef, TryFrom};
+use std::fmt;
 use std::hash::Hash;
 
 #[doc(hidden)]
@@ -31,12 +32,18 @@ pub fn abort_and_exit(msg: &str) -> ! {
 }
 
 pub mod pybool;
+pub mod pybuffer;
+pub mod pybytes;
+pub mod pydatetime;
 pub mod pydict;
 pub mod pylist;
 pub mod pystring;
 pub mod pytuple;
 
 use self::pybool::PyBool;
+use self::pybuffer::PyBuffer;
+use self::pybytes::PyBytes;
+use self::pydatetime::{PyDate, PyDateTime, PyTime};
 use self::pydict::{PyDict, PyDictKey};
 use self::pylist::PyList;
 use self::pystring::PyString;
@@ -69,9 +76,14 @@ pub enum PyArg {
     F64(f64),
     PyBool(PyBool),
     PyString(PyString),
+    PyBytes(PyBytes),
     PyTuple(*mut PyTuple),
     PyList(*mut PyList),
     PyDict(*mut size_t),
+    PyBuffer(*mut PyBuffer),
+    PyDate(PyDate),
+    PyTime(PyTime),
+    PyDateTime(PyDateTime),
     None,
 }
 
@@ -79,6 +91,64 @@ impl PyArg {
     pub fn into_raw(self) -> *mut PyArg {
         Box::into_raw(Box::new(self))
     }
+
+    /// A short, stable name for the variant currently held. Used to build
+    /// [`PyArgError`] messages without resorting to `{:?}` on the whole value (which would
+    /// also print the payload).
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            PyArg::I64(_) => "i64",
+            PyArg::I32(_) => "i32",
+            PyArg::I16(_) => "i16",
+            PyArg::I8(_) => "i8",
+            PyArg::U64(_) => "u64",
+            PyArg::U32(_) => "u32",
+            PyArg::U16(_) => "u16",
+            PyArg::U8(_) => "u8",
+            PyArg::F32(_) => "f32",
+            PyArg::F64(_) => "f64",
+            PyArg::PyBool(_) => "PyBool",
+            PyArg::PyString(_) => "PyString",
+            PyArg::PyBytes(_) => "PyBytes",
+            PyArg::PyTuple(_) => "PyTuple",
+            PyArg::PyList(_) => "PyList",
+            PyArg::PyDict(_) => "PyDict",
+            PyArg::PyBuffer(_) => "PyBuffer",
+            PyArg::PyDate(_) => "PyDate",
+            PyArg::PyTime(_) => "PyTime",
+            PyArg::PyDateTime(_) => "PyDateTime",
+            PyArg::None => "None",
+        }
+    }
+}
+
+/// Error returned by the fallible `TryFrom<PyArg>` conversions and the
+/// `pyarg_try_extract_*` FFI functions, recording which variant was expected and which one
+/// was actually found. Unlike [`abort_and_exit`], producing one of these never terminates
+/// the process, so the Python side can turn it into a `TypeError` instead of crashing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PyArgError {
+    expected: &'static str,
+    found: &'static str,
+}
+
+impl PyArgError {
+    fn new(expected: &'static str, found: &PyArg) -> PyArgError {
+        PyArgError {
+            expected,
+            found: found.kind(),
+        }
+    }
+}
+
+impl fmt::Display for PyArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a {} while destructuring PyArg enum, found a {}",
+            self.expected, self.found
+        )
+    }
 }
 
 macro_rules! pyarg_conversions {
@@ -101,14 +171,21 @@ macro_rules! pyarg_conversions {
             }
         }
 
+        impl TryFrom<PyArg> for $type {
+            type Error = PyArgError;
+            fn try_from(a: PyArg) -> Result<$type, PyArgError> {
+                match a {
+                    $variant(v) => Ok(v),
+                    ref other => Err(PyArgError::new($repr, other)),
+                }
+            }
+        }
+
         impl From<PyArg> for $type {
             fn from(a: PyArg) -> $type {
-                match a {
-                    $variant(v) => v,
-                    _ => {
-                        let msg = format!("expected a {} while destructuring PyArg enum", $repr);
-                        abort_and_exit(msg.as_str());
-                    }
+                match <$type>::try_from(a) {
+                    Ok(v) => v,
+                    Err(err) => abort_and_exit(&err.to_string()),
                 }
             }
         }
@@ -132,14 +209,21 @@ macro_rules! pyarg_conversions {
             }
         }
 
+        impl TryFrom<PyArg> for $type {
+            type Error = PyArgError;
+            fn try_from(a: PyArg) -> Result<$type, PyArgError> {
+                match a {
+                    $variant(v) => Ok(unsafe { *(Box::from_raw(v)) }),
+                    ref other => Err(PyArgError::new($repr, other)),
+                }
+            }
+        }
+
         impl From<PyArg> for $type {
             fn from(a: PyArg) -> $type {
-                match a {
-                    $variant(v) => unsafe { *(Box::from_raw(v)) },
-                    _ => {
-                        let msg = format!("expected a {} while destructuring PyArg enum", $repr);
-                        abort_and_exit(msg.as_str());
-                    }
+                match <$type>::try_from(a) {
+                    Ok(v) => v,
+                    Err(err) => abort_and_exit(&err.to_string()),
                 }
             }
         }
@@ -158,8 +242,13 @@ pyarg_conversions!(f32; PyArg::F32; "f32");
 pyarg_conversions!(f64; PyArg::F64; "f64");
 pyarg_conversions!(PyBool; PyArg::PyBool; "PyBool");
 pyarg_conversions!(PyString; PyArg::PyString; "PyString");
+pyarg_conversions!(PyBytes; PyArg::PyBytes; "PyBytes");
 pyarg_conversions!(BOXED PyTuple; PyArg::PyTuple; "PyTuple");
 pyarg_conversions!(BOXED PyList; PyArg::PyList; "PyList");
+pyarg_conversions!(BOXED PyBuffer; PyArg::PyBuffer; "PyBuffer");
+pyarg_conversions!(PyDate; PyArg::PyDate; "PyDate");
+pyarg_conversions!(PyTime; PyArg::PyTime; "PyTime");
+pyarg_conversions!(PyDateTime; PyArg::PyDateTime; "PyDateTime");
 
 impl<K> AsRef<PyDict<K>> for PyArg
 where
@@ -230,11 +319,34 @@ where
 
 // Conversions from PyArg to <T>
 
+impl TryFrom<PyArg> for String {
+    type Error = PyArgError;
+    fn try_from(a: PyArg) -> Result<String, PyArgError> {
+        match a {
+            PyArg::PyString(v) => Ok(v.to_string()),
+            ref other => Err(PyArgError::new("PyString", other)),
+        }
+    }
+}
+
 impl From<PyArg> for String {
     fn from(a: PyArg) -> String {
+        match String::try_from(a) {
+            Ok(v) => v,
+            Err(err) => abort_and_exit(&err.to_string()),
+        }
+    }
+}
+
+impl<K> TryFrom<PyArg> for PyDict<K>
+where
+    K: Eq + Hash + PyDictKey,
+{
+    type Error = PyArgError;
+    fn try_from(a: PyArg) -> Result<PyDict<K>, PyArgError> {
         match a {
-            PyArg::PyString(v) => v.to_string(),
-            _ => abort_and_exit("expected a PyString while destructuring PyArg enum"),
+            PyArg::PyDict(v) => Ok(unsafe { *(Box::from_raw(v as *mut PyDict<K>)) }),
+            ref other => Err(PyArgError::new("PyDict", other)),
         }
     }
 }
@@ -244,9 +356,9 @@ where
     K: Eq + Hash + PyDictKey,
 {
     fn from(a: PyArg) -> PyDict<K> {
-        match a {
-            PyArg::PyDict(v) => unsafe { *(Box::from_raw(v as *mut PyDict<K>)) },
-            _ => abort_and_exit("expected a PyDict while destructuring PyArg enum"),
+        match PyDict::<K>::try_from(a) {
+            Ok(v) => v,
+            Err(err) => abort_and_exit(&err.to_string()),
         }
     }
 }
@@ -291,6 +403,13 @@ pub unsafe extern "C" fn pyarg_from_str(e: *const c_char) -> *mut PyArg {
     Box::into_raw(Box::new(PyArg::PyString(e)))
 }
 
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_from_bytes(ptr: *const u8, len: size_t) -> *mut PyArg {
+    let e = PyBytes::from_raw(ptr, len);
+    Box::into_raw(Box::new(PyArg::PyBytes(e)))
+}
+
 #[doc(hidden)]
 #[no_mangle]
 pub extern "C" fn pyarg_from_pytuple(e: *mut PyTuple) -> *mut PyArg {
@@ -311,6 +430,64 @@ pub extern "C" fn pyarg_from_pydict(e: *mut size_t) -> *mut PyArg {
     Box::into_raw(Box::new(PyArg::PyDict(e)))
 }
 
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pyarg_from_buffer(e: *mut PyBuffer) -> *mut PyArg {
+    Box::into_raw(Box::new(PyArg::PyBuffer(e)))
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pyarg_from_date(year: i32, month: u8, day: u8) -> *mut PyArg {
+    let e = PyDate { year, month, day };
+    Box::into_raw(Box::new(PyArg::PyDate(e)))
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pyarg_from_time(
+    hour: u8,
+    minute: u8,
+    second: u8,
+    microsecond: u32,
+    utc_offset_secs: i32,
+) -> *mut PyArg {
+    let e = PyTime {
+        hour,
+        minute,
+        second,
+        microsecond,
+        utc_offset_secs,
+    };
+    Box::into_raw(Box::new(PyArg::PyTime(e)))
+}
+
+#[doc(hidden)]
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn pyarg_from_datetime(
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    microsecond: u32,
+    utc_offset_secs: i32,
+) -> *mut PyArg {
+    let e = PyDateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        microsecond,
+        utc_offset_secs,
+    };
+    Box::into_raw(Box::new(PyArg::PyDateTime(e)))
+}
+
 // Extract owned args, no copies:
 #[doc(hidden)]
 #[no_mangle]
@@ -381,6 +558,16 @@ pub unsafe extern "C" fn pyarg_extract_owned_str(e: *mut PyArg) -> *mut PyString
     }
 }
 
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_extract_owned_bytes(e: *mut PyArg) -> *mut PyBytes {
+    let e = *(Box::from_raw(e));
+    match e {
+        PyArg::PyBytes(val) => val.into_raw(),
+        _ => abort_and_exit("failed while trying to extract a PyBytes"),
+    }
+}
+
 #[doc(hidden)]
 #[no_mangle]
 pub unsafe extern "C" fn pyarg_extract_owned_tuple(e: *mut PyArg) -> *mut PyTuple {
@@ -410,3 +597,228 @@ pub unsafe extern "C" fn pyarg_extract_owned_dict(e: *mut PyArg) -> *mut size_t
         _ => abort_and_exit("failed while trying to extract a PyDict"),
     }
 }
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_extract_owned_buffer(e: *mut PyArg) -> *mut PyBuffer {
+    let e = *(Box::from_raw(e));
+    match e {
+        PyArg::PyBuffer(val) => val,
+        _ => abort_and_exit("failed while trying to extract a PyBuffer"),
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_extract_owned_date(e: *mut PyArg) -> *mut PyDate {
+    let e = *(Box::from_raw(e));
+    match e {
+        PyArg::PyDate(val) => val.into_raw(),
+        _ => abort_and_exit("failed while trying to extract a PyDate"),
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_extract_owned_time(e: *mut PyArg) -> *mut PyTime {
+    let e = *(Box::from_raw(e));
+    match e {
+        PyArg::PyTime(val) => val.into_raw(),
+        _ => abort_and_exit("failed while trying to extract a PyTime"),
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_extract_owned_datetime(e: *mut PyArg) -> *mut PyDateTime {
+    let e = *(Box::from_raw(e));
+    match e {
+        PyArg::PyDateTime(val) => val.into_raw(),
+        _ => abort_and_exit("failed while trying to extract a PyDateTime"),
+    }
+}
+
+// Fallible extraction, no aborts: writes through `out` and returns `0` on success or `-1` on
+// a type mismatch, so callers (e.g. a Python-side wrapper) can raise instead of crashing.
+
+/// Frees whatever a mismatched `PyArg` was actually holding, so a failed `pyarg_try_extract_*`
+/// call below doesn't leak the nested value its wildcard arm would otherwise just drop on the
+/// floor. `PyTuple`/`PyList`/`PyBuffer` each expose a plain `from_ptr` that reconstructs the
+/// owned value so it drops correctly; the scalar/by-value variants need no cleanup at all.
+/// `PyDict` is a known gap: it's stored behind a type-erased `*mut size_t` and can only be freed
+/// with the `PyDictK` tag that `pydict_free` takes as a separate argument, which isn't available
+/// here, so a mismatched `PyDict` still leaks. That's narrower than the leak this helper closes
+/// for every other container type.
+unsafe fn free_mismatched_pyarg(e: PyArg) {
+    match e {
+        PyArg::PyTuple(val) => {
+            PyTuple::from_ptr(val);
+        }
+        PyArg::PyList(val) => {
+            PyList::from_ptr(val);
+        }
+        PyArg::PyBuffer(val) => {
+            PyBuffer::from_ptr(val);
+        }
+        _ => {}
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_int(e: *mut PyArg, out: *mut i64) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::I64(val) => {
+            *out = val;
+            0
+        }
+        PyArg::I32(val) => {
+            *out = i64::from(val);
+            0
+        }
+        PyArg::I16(val) => {
+            *out = i64::from(val);
+            0
+        }
+        PyArg::I8(val) => {
+            *out = i64::from(val);
+            0
+        }
+        PyArg::U32(val) => {
+            *out = i64::from(val);
+            0
+        }
+        PyArg::U16(val) => {
+            *out = i64::from(val);
+            0
+        }
+        PyArg::U8(val) => {
+            *out = i64::from(val);
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_ulonglong(e: *mut PyArg, out: *mut u64) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::U64(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_float(e: *mut PyArg, out: *mut f32) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::F32(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_double(e: *mut PyArg, out: *mut f64) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::F64(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_bool(e: *mut PyArg, out: *mut *mut PyBool) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::PyBool(val) => {
+            *out = val.into_raw();
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_str(e: *mut PyArg, out: *mut *mut PyString) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::PyString(val) => {
+            *out = val.into_raw();
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_tuple(e: *mut PyArg, out: *mut *mut PyTuple) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::PyTuple(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_list(e: *mut PyArg, out: *mut *mut PyList) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::PyList(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_try_extract_dict(e: *mut PyArg, out: *mut *mut size_t) -> i32 {
+    match *(Box::from_raw(e)) {
+        PyArg::PyDict(val) => {
+            *out = val;
+            0
+        }
+        other => {
+            free_mismatched_pyarg(other);
+            -1
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Bridges Python's buffer protocol (PEP 3118) into rustypy's own numeric type tags.
+//!
+//! A Python buffer/memoryview describes its element type with a `struct`-module format
+//! character rather than a `PyDictK` discriminant, so before a buffer can be zero-copy
+//! ingested into a `PyDict`/`PyList` monomorphization, the format string has to be mapped
+//! onto an [`ElementType`]. [`ElementType::from_format`] does that mapping; unrecognized
+//! formats come back as [`ElementType::Unknown`] so a caller can reject the buffer instead
+//! of guessing at its layout.
+//!
+//! [`PyBuffer`] is the type that actually crosses the FFI boundary: a single contiguous
+//! byte allocation plus the metadata (`itemsize`, element count, format char, optional
+//! `shape`/`strides`) needed to reinterpret it, so a homogeneous numeric `Vec<T>` makes the
+//! trip as one memcpy-able block instead of being boxed element by element into a `PyList`.
+
+use libc::{c_char, size_t};
+
+use std::mem;
+use std::slice;
+
+/// The element type described by a buffer's `struct`-module format string, with enough
+/// signedness/width information to pick the matching `PyDictK`/`PyList` monomorphization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    Bool,
+    /// The format string didn't match any format character this bridge understands.
+    Unknown,
+}
+
+impl ElementType {
+    /// Parses a `struct`-module format string (as found in `memoryview.format`) into an
+    /// [`ElementType`]. Leading byte-order markers (`@`, `=`, `<`, `>`) are stripped before
+    /// the format character itself is matched; anything left over, or more than one trailing
+    /// character, yields [`ElementType::Unknown`].
+    pub fn from_format(format: &str) -> ElementType {
+        let format = format.trim_start_matches(|c| c == '@' || c == '=' || c == '<' || c == '>');
+        let mut chars = format.chars();
+        let code = match chars.next() {
+            Some(c) => c,
+            None => return ElementType::Unknown,
+        };
+        if chars.next().is_some() {
+            return ElementType::Unknown;
+        }
+        match code {
+            'b' => ElementType::I8,
+            'B' => ElementType::U8,
+            'h' => ElementType::I16,
+            'H' => ElementType::U16,
+            'i' | 'l' => ElementType::I32,
+            'I' | 'L' => ElementType::U32,
+            'q' => ElementType::I64,
+            'Q' => ElementType::U64,
+            '?' => ElementType::Bool,
+            _ => ElementType::Unknown,
+        }
+    }
+}
+
+/// A contiguous block of homogeneous numeric data, modeled on PEP 3118 / the buffer protocol
+/// (as exposed by `PyBuffer::get` in the ecosystem). Crosses the FFI boundary as a single
+/// allocation, plus `len` (element count), `itemsize`, a `struct`-module format character,
+/// and optional `shape`/`strides` for multi-dimensional buffers.
+///
+/// # Safety
+/// Like the other pytypes, `PyBuffer` must be passed between Rust and Python as a raw
+/// pointer, obtained with [`into_raw`](PyBuffer::into_raw) and consumed with the unsafe
+/// [`from_ptr`](PyBuffer::from_ptr).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PyBuffer {
+    data: Vec<u8>,
+    len: usize,
+    itemsize: usize,
+    format: char,
+    shape: Option<Vec<usize>>,
+    strides: Option<Vec<isize>>,
+}
+
+impl PyBuffer {
+    /// Builds a `PyBuffer` by copying the bytes of a `&[T]` into a single contiguous
+    /// allocation. `format` should be the `struct`-module format character describing `T`
+    /// (see [`ElementType::from_format`]).
+    pub fn from_slice<T: Copy>(data: &[T], format: char) -> PyBuffer {
+        let itemsize = mem::size_of::<T>();
+        let len = data.len();
+        let bytes =
+            unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, len * itemsize) };
+        PyBuffer {
+            data: bytes.to_vec(),
+            len,
+            itemsize,
+            format,
+            shape: None,
+            strides: None,
+        }
+    }
+
+    /// Reinterprets the buffer's bytes as a `&[T]`, with no copy. The caller must pass a `T`
+    /// whose size matches [`itemsize`](PyBuffer::itemsize); this is checked with an
+    /// assertion, but the finer-grained layout match against `format` is not.
+    pub unsafe fn as_slice<T: Copy>(&self) -> &[T] {
+        assert_eq!(
+            mem::size_of::<T>(),
+            self.itemsize,
+            "PyBuffer element size mismatch"
+        );
+        slice::from_raw_parts(self.data.as_ptr() as *const T, self.len)
+    }
+
+    /// Attaches shape/strides metadata, e.g. for a multi-dimensional `memoryview`.
+    pub fn with_shape(mut self, shape: Vec<usize>, strides: Vec<isize>) -> PyBuffer {
+        self.shape = Some(shape);
+        self.strides = Some(strides);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn itemsize(&self) -> usize {
+        self.itemsize
+    }
+
+    pub fn format(&self) -> char {
+        self.format
+    }
+
+    pub fn shape(&self) -> Option<&[usize]> {
+        self.shape.as_ref().map(|v| v.as_slice())
+    }
+
+    pub fn strides(&self) -> Option<&[isize]> {
+        self.strides.as_ref().map(|v| v.as_slice())
+    }
+
+    /// Get a PyBuffer from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyBuffer) -> PyBuffer {
+        *(Box::from_raw(ptr))
+    }
+
+    /// Return a PyBuffer as a raw pointer.
+    pub fn into_raw(self) -> *mut PyBuffer {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pybuffer_new(
+    ptr: *const u8,
+    len: size_t,
+    itemsize: size_t,
+    format: c_char,
+) -> *mut PyBuffer {
+    let data = slice::from_raw_parts(ptr, len * itemsize).to_vec();
+    PyBuffer {
+        data,
+        len,
+        itemsize,
+        format: format as u8 as char,
+        shape: None,
+        strides: None,
+    }
+    .into_raw()
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pybuffer_free(ptr: *mut PyBuffer) {
+    if ptr.is_null() {
+        return;
+    }
+    Box::from_raw(ptr);
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pybuffer_len(buffer: &PyBuffer) -> size_t {
+    buffer.len
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pybuffer_itemsize(buffer: &PyBuffer) -> size_t {
+    buffer.itemsize
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pybuffer_format(buffer: &PyBuffer) -> c_char {
+    buffer.format as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pybuffer_as_ptr(buffer: &PyBuffer) -> *const u8 {
+    buffer.data.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_every_known_format_character() {
+        assert_eq!(ElementType::from_format("b"), ElementType::I8);
+        assert_eq!(ElementType::from_format("B"), ElementType::U8);
+        assert_eq!(ElementType::from_format("h"), ElementType::I16);
+        assert_eq!(ElementType::from_format("H"), ElementType::U16);
+        assert_eq!(ElementType::from_format("i"), ElementType::I32);
+        assert_eq!(ElementType::from_format("l"), ElementType::I32);
+        assert_eq!(ElementType::from_format("I"), ElementType::U32);
+        assert_eq!(ElementType::from_format("L"), ElementType::U32);
+        assert_eq!(ElementType::from_format("q"), ElementType::I64);
+        assert_eq!(ElementType::from_format("Q"), ElementType::U64);
+        assert_eq!(ElementType::from_format("?"), ElementType::Bool);
+    }
+
+    #[test]
+    fn strips_byte_order_markers() {
+        assert_eq!(ElementType::from_format("<i"), ElementType::I32);
+        assert_eq!(ElementType::from_format(">Q"), ElementType::U64);
+        assert_eq!(ElementType::from_format("@b"), ElementType::I8);
+        assert_eq!(ElementType::from_format("=?"), ElementType::Bool);
+    }
+
+    #[test]
+    fn unrecognized_formats_are_explicit() {
+        assert_eq!(ElementType::from_format("f"), ElementType::Unknown);
+        assert_eq!(ElementType::from_format("2i"), ElementType::Unknown);
+        assert_eq!(ElementType::from_format(""), ElementType::Unknown);
+    }
+
+    #[test]
+    fn round_trips_through_from_slice_and_as_slice() {
+        let values: Vec<i64> = vec![1, 2, 3, 4];
+        let buffer = PyBuffer::from_slice(&values, 'q');
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.itemsize(), mem::size_of::<i64>());
+        assert_eq!(buffer.format(), 'q');
+        let back = unsafe { buffer.as_slice::<i64>() };
+        assert_eq!(back, values.as_slice());
+    }
+
+    #[test]
+    fn carries_optional_shape_and_strides() {
+        let values: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0];
+        let buffer = PyBuffer::from_slice(&values, 'd').with_shape(vec![2, 2], vec![16, 8]);
+        assert_eq!(buffer.shape(), Some(&[2, 2][..]));
+        assert_eq!(buffer.strides(), Some(&[16, 8][..]));
+    }
+}
@@ -0,0 +1,101 @@
+//! An analog of Python `bytes`, backed by an owned `Vec<u8>` instead of a `CString`, so raw
+//! binary payloads (non-UTF-8 data, interior NUL bytes) round-trip intact across the FFI
+//! boundary. Mirrors the way PyO3 keeps `PyBytes`/`PyByteArray` separate from `PyString`.
+//!
+//! To return to Python you must use the ```into_raw``` method and return a raw pointer, same
+//! as [PyString](../pystring/struct.PyString.html).
+//!
+//! # Safety
+//! When passed from Python you can convert from PyBytes to an owned `Vec<u8>`
+//! (```from_ptr_to_vec``` method) or back to an owned PyBytes (```from_ptr``` method), and
+//! borrow a PyBytes as a `&[u8]` slice (```as_slice``` method). The two pointer conversions
+//! are unsafe as they require dereferencing a raw pointer.
+
+use libc::size_t;
+use std::mem;
+use std::slice;
+
+/// An analog of a Python `bytes` object.
+///
+/// Read the [module docs](index.html) for more information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PyBytes {
+    _inner: Vec<u8>,
+}
+
+impl PyBytes {
+    /// Get a PyBytes from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyBytes) -> PyBytes {
+        *Box::from_raw(ptr)
+    }
+    /// Constructs an owned Vec<u8> from a raw pointer.
+    pub unsafe fn from_ptr_to_vec(ptr: *mut PyBytes) -> Vec<u8> {
+        let pybytes = *(Box::from_raw(ptr));
+        pybytes._inner
+    }
+    /// Returns PyBytes as a raw pointer. Use this whenever you want to return
+    /// a PyBytes to Python.
+    pub fn into_raw(self) -> *mut PyBytes {
+        Box::into_raw(Box::new(self))
+    }
+    /// Copies `len` bytes starting at `ptr` into an owned PyBytes.
+    pub unsafe fn from_raw(ptr: *const u8, len: size_t) -> PyBytes {
+        PyBytes {
+            _inner: slice::from_raw_parts(ptr, len).to_vec(),
+        }
+    }
+    /// Borrows the underlying bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self._inner
+    }
+    /// Copies a byte slice into an owned PyBytes.
+    pub fn from_slice(data: &[u8]) -> PyBytes {
+        PyBytes {
+            _inner: data.to_vec(),
+        }
+    }
+}
+
+/// Destructs the PyBytes, mostly to be used from Python.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pybytes_free(ptr: *mut PyBytes) {
+    if ptr.is_null() {
+        return;
+    }
+    Box::from_raw(ptr);
+}
+
+/// Creates a PyBytes wrapper from a raw byte pointer and length.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pybytes_new(ptr: *const u8, len: size_t) -> *mut PyBytes {
+    PyBytes::from_raw(ptr, len).into_raw()
+}
+
+/// Consumes the wrapper, writes the length of the underlying bytes through `len_out` and
+/// returns a raw pointer to them. The caller takes ownership of the returned allocation
+/// (`*len_out` bytes long).
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pybytes_get_bytes(ptr: *mut PyBytes, len_out: *mut size_t) -> *mut u8 {
+    let pybytes = PyBytes::from_ptr(ptr);
+    let mut buf = pybytes._inner.into_boxed_slice();
+    *len_out = buf.len();
+    let data_ptr = buf.as_mut_ptr();
+    mem::forget(buf);
+    data_ptr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pybytes_operations() {
+        let source: Vec<u8> = vec![0u8, 159, 146, 150, 0, 255];
+        let owned_pybytes = PyBytes::from_slice(&source).into_raw();
+        let back_from_py = unsafe { PyBytes::from_ptr_to_vec(owned_pybytes) };
+        assert_eq!(back_from_py, source);
+    }
+}
@@ -0,0 +1,309 @@
+//! Analogs of Python's `datetime.date`, `datetime.time` and `datetime.datetime`, so calendar
+//! values can cross the FFI boundary as broken-down components instead of being manually
+//! serialized to a string and parsed back on each side.
+//!
+//! Each type is a small, `Copy`-able, `#[repr(C)]` struct carrying the same component fields
+//! PyO3's `make_time`/`make_datetime` helpers take, plus an optional UTC offset in seconds.
+//! Since C has no `Option`, the offset uses [`NO_UTC_OFFSET`] as a sentinel meaning "naive,
+//! no tzinfo" rather than an actual offset of zero seconds (UTC).
+//!
+//! As with [PyBool](../pybool/struct.PyBool.html), values are passed across the FFI boundary
+//! as boxed raw pointers obtained with `into_raw` and consumed with the unsafe `from_ptr`.
+
+use libc::c_char;
+
+/// Sentinel [`PyTime::utc_offset_secs`]/[`PyDateTime::utc_offset_secs`] value meaning the
+/// time is naive (no tzinfo attached), as opposed to an actual UTC offset of zero seconds.
+pub const NO_UTC_OFFSET: i32 = i32::min_value();
+
+/// A calendar date, analogous to `datetime.date`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PyDate {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl PyDate {
+    /// Get a PyDate from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyDate) -> PyDate {
+        *(Box::from_raw(ptr))
+    }
+    /// Returns PyDate as a raw pointer. Use this whenever you want to return
+    /// a PyDate to Python.
+    pub fn into_raw(self) -> *mut PyDate {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+/// A time of day, analogous to `datetime.time`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PyTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+    /// UTC offset in seconds, or [`NO_UTC_OFFSET`] if this time is naive.
+    pub utc_offset_secs: i32,
+}
+
+impl PyTime {
+    /// Get a PyTime from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyTime) -> PyTime {
+        *(Box::from_raw(ptr))
+    }
+    /// Returns PyTime as a raw pointer. Use this whenever you want to return
+    /// a PyTime to Python.
+    pub fn into_raw(self) -> *mut PyTime {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+/// A calendar date and time, analogous to `datetime.datetime`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PyDateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+    /// UTC offset in seconds, or [`NO_UTC_OFFSET`] if this datetime is naive.
+    pub utc_offset_secs: i32,
+}
+
+impl PyDateTime {
+    /// Get a PyDateTime from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyDateTime) -> PyDateTime {
+        *(Box::from_raw(ptr))
+    }
+    /// Returns PyDateTime as a raw pointer. Use this whenever you want to return
+    /// a PyDateTime to Python.
+    pub fn into_raw(self) -> *mut PyDateTime {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydate_new(year: i32, month: u8, day: u8) -> *mut PyDate {
+    PyDate { year, month, day }.into_raw()
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydate_free(ptr: *mut PyDate) {
+    if ptr.is_null() {
+        return;
+    }
+    Box::from_raw(ptr);
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_new(
+    hour: u8,
+    minute: u8,
+    second: u8,
+    microsecond: u32,
+    utc_offset_secs: i32,
+) -> *mut PyTime {
+    PyTime {
+        hour,
+        minute,
+        second,
+        microsecond,
+        utc_offset_secs,
+    }
+    .into_raw()
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pytime_free(ptr: *mut PyTime) {
+    if ptr.is_null() {
+        return;
+    }
+    Box::from_raw(ptr);
+}
+
+#[doc(hidden)]
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn pydatetime_new(
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    microsecond: u32,
+    utc_offset_secs: i32,
+) -> *mut PyDateTime {
+    PyDateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        microsecond,
+        utc_offset_secs,
+    }
+    .into_raw()
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydatetime_free(ptr: *mut PyDateTime) {
+    if ptr.is_null() {
+        return;
+    }
+    Box::from_raw(ptr);
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydate_get_year(date: &PyDate) -> i32 {
+    date.year
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydate_get_month(date: &PyDate) -> c_char {
+    date.month as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydate_get_day(date: &PyDate) -> c_char {
+    date.day as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_get_hour(time: &PyTime) -> c_char {
+    time.hour as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_get_minute(time: &PyTime) -> c_char {
+    time.minute as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_get_second(time: &PyTime) -> c_char {
+    time.second as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_get_microsecond(time: &PyTime) -> u32 {
+    time.microsecond
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pytime_get_utc_offset_secs(time: &PyTime) -> i32 {
+    time.utc_offset_secs
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_year(dt: &PyDateTime) -> i32 {
+    dt.year
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_month(dt: &PyDateTime) -> c_char {
+    dt.month as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_day(dt: &PyDateTime) -> c_char {
+    dt.day as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_hour(dt: &PyDateTime) -> c_char {
+    dt.hour as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_minute(dt: &PyDateTime) -> c_char {
+    dt.minute as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_second(dt: &PyDateTime) -> c_char {
+    dt.second as c_char
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_microsecond(dt: &PyDateTime) -> u32 {
+    dt.microsecond
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn pydatetime_get_utc_offset_secs(dt: &PyDateTime) -> i32 {
+    dt.utc_offset_secs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_round_trips_through_raw_pointer() {
+        let date = PyDate {
+            year: 2026,
+            month: 7,
+            day: 29,
+        };
+        let ptr = date.into_raw();
+        let back = unsafe { PyDate::from_ptr(ptr) };
+        assert_eq!(back, date);
+    }
+
+    #[test]
+    fn naive_time_uses_the_sentinel_offset() {
+        let time = PyTime {
+            hour: 12,
+            minute: 30,
+            second: 0,
+            microsecond: 0,
+            utc_offset_secs: NO_UTC_OFFSET,
+        };
+        assert_eq!(time.utc_offset_secs, NO_UTC_OFFSET);
+    }
+
+    #[test]
+    fn datetime_round_trips_with_a_utc_offset() {
+        let dt = PyDateTime {
+            year: 2026,
+            month: 7,
+            day: 29,
+            hour: 9,
+            minute: 0,
+            second: 0,
+            microsecond: 0,
+            utc_offset_secs: 3600,
+        };
+        let ptr = dt.into_raw();
+        let back = unsafe { PyDateTime::from_ptr(ptr) };
+        assert_eq!(back, dt);
+    }
+}
@@ -56,7 +56,7 @@
 //! to convert a PyDict to a Rust native type. Check the macro documentation for more info.
 
 use super::{abort_and_exit, PyArg, PyBool, PyList, PyString, PyTuple};
-use libc::size_t;
+use libc::{c_int, size_t};
 
 use std::collections::hash_map::Drain;
 use std::collections::HashMap;
@@ -142,6 +142,10 @@ where
         self._inner.get_mut(k)
     }
 
+    fn get_pyarg(&self, k: &K) -> Option<&PyArg> {
+        self._inner.get(k)
+    }
+
     /// Clears the map, returning all key-value pairs as an iterator.
     /// Keeps the allocated memory for reuse.
     #[doc(hidden)]
@@ -289,6 +293,14 @@ pub extern "C" fn pydict_new(k_type: &PyDictK) -> *mut size_t {
             let d: PyDict<PyBool> = PyDict::new();
             d.into_raw() as *mut size_t
         }
+        PyDictK::F32 => {
+            let d: PyDict<PyFloatKey32> = PyDict::new();
+            d.into_raw() as *mut size_t
+        }
+        PyDictK::F64 => {
+            let d: PyDict<PyFloatKey64> = PyDict::new();
+            d.into_raw() as *mut size_t
+        }
     }
 }
 
@@ -372,6 +384,18 @@ pub unsafe extern "C" fn pydict_insert(
             let value = *(Box::from_raw(value));
             dict.insert(key, value);
         }
+        PyDictK::F32 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey32>);
+            let key = PyFloatKey32::from(_match_pyarg_in!(key; F32));
+            let value = *(Box::from_raw(value));
+            dict.insert(key, value);
+        }
+        PyDictK::F64 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey64>);
+            let key = PyFloatKey64::from(_match_pyarg_in!(key; F64));
+            let value = *(Box::from_raw(value));
+            dict.insert(key, value);
+        }
     };
 }
 
@@ -459,6 +483,14 @@ pub unsafe extern "C" fn pydict_get_drain(dict: *mut size_t, k_type: &PyDictK) -
             let dict = &mut *(dict as *mut PyDict<PyBool>);
             Box::into_raw(Box::new(dict.drain())) as *mut size_t
         }
+        PyDictK::F32 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey32>);
+            Box::into_raw(Box::new(dict.drain())) as *mut size_t
+        }
+        PyDictK::F64 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey64>);
+            Box::into_raw(Box::new(dict.drain())) as *mut size_t
+        }
     }
 }
 
@@ -578,6 +610,20 @@ pub unsafe extern "C" fn pydict_drain_element(
                 None => _get_null(),
             }
         }
+        PyDictK::F32 => {
+            let iter = &mut *(iter as *mut Drain<PyFloatKey32, PyArg>);
+            match iter.next() {
+                Some(val) => PyDictPair::kv_return_tuple(PyArg::F32(f32::from(val.0)), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::F64 => {
+            let iter = &mut *(iter as *mut Drain<PyFloatKey64, PyArg>);
+            match iter.next() {
+                Some(val) => PyDictPair::kv_return_tuple(PyArg::F64(f64::from(val.0)), val.1),
+                None => _get_null(),
+            }
+        }
     }
 }
 
@@ -694,94 +740,1006 @@ pub unsafe extern "C" fn pydict_get_mut_element(
                 None => _get_null() as *mut size_t,
             }
         }
+        PyDictK::F32 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey32>);
+            let key = PyFloatKey32::from(*(Box::from_raw(key as *mut f32)));
+            match dict.get_mut_pyarg(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::F64 => {
+            let dict = &mut *(dict as *mut PyDict<PyFloatKey64>);
+            let key = PyFloatKey64::from(*(Box::from_raw(key as *mut f64)));
+            match dict.get_mut_pyarg(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+    }
+}
+
+/// Looks up `key` in the type-erased dict and, if present, writes an *owned* copy of the
+/// value through `out`. Modeled on CPython 3.13's `PyDict_GetItemRef`: unlike
+/// [`pydict_get_mut_element`], the returned value does not borrow from the dict, so it
+/// remains valid even if the entry is concurrently removed or the dict is freed (the
+/// invariant free-threaded/no-GIL builds require).
+///
+/// Returns `1` if the key was found (`out` written), `0` if not found (`out` untouched),
+/// or `-1` on error (null `dict`/`out`, or a key of the wrong `PyDictK`).
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydict_get_item(
+    dict: *mut size_t,
+    k_type: &PyDictK,
+    key: *mut PyArg,
+    out: *mut *mut PyArg,
+) -> c_int {
+    if dict.is_null() || out.is_null() {
+        return -1;
+    }
+    macro_rules! _lookup {
+        ($K:ty; $V:ident) => {{
+            let dict = &*(dict as *mut PyDict<$K>);
+            let key = match *(Box::from_raw(key)) {
+                PyArg::$V(val) => <$K>::from(val),
+                _ => return -1,
+            };
+            match dict.get_pyarg(&key) {
+                Some(val) => {
+                    *out = Box::into_raw(Box::new(val.clone()));
+                    1
+                }
+                None => 0,
+            }
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _lookup!(i8; I8),
+        PyDictK::I16 => _lookup!(i16; I16),
+        PyDictK::I32 => _lookup!(i32; I32),
+        PyDictK::I64 => _lookup!(i64; I64),
+        PyDictK::U8 => _lookup!(u8; U8),
+        PyDictK::U16 => _lookup!(u16; U16),
+        PyDictK::U32 => _lookup!(u32; U32),
+        PyDictK::U64 => _lookup!(u64; U64),
+        PyDictK::PyString => _lookup!(PyString; PyString),
+        PyDictK::PyBool => _lookup!(PyBool; PyBool),
+        PyDictK::F32 => _lookup!(PyFloatKey32; F32),
+        PyDictK::F64 => _lookup!(PyFloatKey64; F64),
     }
 }
 
+/// Releases a value previously written by [`pydict_get_item`].
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydict_item_free(item: *mut PyArg) {
+    if item.is_null() {
+        return;
+    }
+    Box::from_raw(item);
+}
+
 #[doc(hidden)]
 #[no_mangle]
 pub unsafe extern "C" fn pydict_free(dict: *mut size_t, k_type: &PyDictK) {
     if dict.is_null() {
         return;
     }
+    free_pydict_for_key_type(dict, k_type)
+}
+
+/// Declares the full list of types a `PyDict` key may take, in exactly one place.
+///
+/// Each tuple is `(discriminant, PyDictK variant, Rust key type)`. The macro expands into:
+/// the `PyDictK` enum itself, the `key_bound::PyDictKey` marker impls, the
+/// `u32 -> PyDictK` constructor used by `pydict_get_key_type`, and the type-correct
+/// `Box::from_raw` drop arm used by `pydict_free`. Keeping the list in one place makes it
+/// impossible for the drop arm of one key type to silently reinterpret another's allocation.
+macro_rules! define_pydict_keys {
+    ( $( $disc:expr => $variant:ident : $ty:ty ),+ $(,)* ) => {
+        /// Types allowed as PyDict key values.
+        pub enum PyDictK {
+            $( $variant, )+
+        }
+
+        pub(crate) mod key_bound {
+            use crate::pytypes::pybool::PyBool;
+            use crate::pytypes::pystring::PyString;
+            use super::{PyFloatKey32, PyFloatKey64};
+
+            pub trait PyDictKey {}
+            $( impl PyDictKey for $ty {} )+
+        }
+
+        #[doc(hidden)]
+        #[no_mangle]
+        pub extern "C" fn pydict_get_key_type(k: u32) -> *mut PyDictK {
+            match k {
+                $( $disc => Box::into_raw(Box::new(PyDictK::$variant)), )+
+                _ => abort_and_exit("type not supported as PyDict key type"),
+            }
+        }
+
+        unsafe fn free_pydict_for_key_type(dict: *mut size_t, k_type: &PyDictK) {
+            match *(k_type) {
+                $( PyDictK::$variant => {
+                    Box::from_raw(dict as *mut PyDict<$ty>);
+                } )+
+            }
+        }
+    };
+}
+
+define_pydict_keys! {
+    1 => U8: u8,
+    2 => I8: i8,
+    3 => I16: i16,
+    4 => U16: u16,
+    5 => I32: i32,
+    6 => U32: u32,
+    7 => I64: i64,
+    8 => U64: u64,
+    11 => PyBool: PyBool,
+    12 => PyString: PyString,
+    13 => F32: PyFloatKey32,
+    14 => F64: PyFloatKey64,
+}
+
+/// A canonical bit pattern used for every `NaN` value of a given width, so that all NaNs
+/// (which are not equal to themselves under IEEE 754) hash and compare as the same key.
+const CANONICAL_NAN_F32_BITS: u32 = 0x7fc0_0000;
+const CANONICAL_NAN_F64_BITS: u64 = 0x7ff8_0000_0000_0000;
+
+/// Hashable, `Eq` wrapper around an `f32` so it can be used as a `PyDict` key.
+///
+/// Equality and hashing operate on the float's raw bit pattern rather than on the float
+/// itself (which is neither `Eq` nor consistently hashable): `+0.0`/`-0.0` are normalized to
+/// the same bit pattern, and every `NaN` collapses to one canonical quiet-NaN bit pattern, so
+/// values that are equal by Python's float semantics hash identically.
+#[derive(Clone, Copy, Debug)]
+pub struct PyFloatKey32(u32);
+
+impl PyFloatKey32 {
+    fn normalize(val: f32) -> u32 {
+        if val.is_nan() {
+            CANONICAL_NAN_F32_BITS
+        } else if val == 0.0 {
+            0.0_f32.to_bits()
+        } else {
+            val.to_bits()
+        }
+    }
+}
+
+impl From<f32> for PyFloatKey32 {
+    fn from(val: f32) -> PyFloatKey32 {
+        PyFloatKey32(PyFloatKey32::normalize(val))
+    }
+}
+
+impl From<PyFloatKey32> for f32 {
+    fn from(key: PyFloatKey32) -> f32 {
+        f32::from_bits(key.0)
+    }
+}
+
+impl PartialEq for PyFloatKey32 {
+    fn eq(&self, other: &PyFloatKey32) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for PyFloatKey32 {}
+
+impl ::std::hash::Hash for PyFloatKey32 {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Hashable, `Eq` wrapper around an `f64`. See [`PyFloatKey32`] for the normalization rules.
+#[derive(Clone, Copy, Debug)]
+pub struct PyFloatKey64(u64);
+
+impl PyFloatKey64 {
+    fn normalize(val: f64) -> u64 {
+        if val.is_nan() {
+            CANONICAL_NAN_F64_BITS
+        } else if val == 0.0 {
+            0.0_f64.to_bits()
+        } else {
+            val.to_bits()
+        }
+    }
+}
+
+impl From<f64> for PyFloatKey64 {
+    fn from(val: f64) -> PyFloatKey64 {
+        PyFloatKey64(PyFloatKey64::normalize(val))
+    }
+}
+
+impl From<PyFloatKey64> for f64 {
+    fn from(key: PyFloatKey64) -> f64 {
+        f64::from_bits(key.0)
+    }
+}
+
+impl PartialEq for PyFloatKey64 {
+    fn eq(&self, other: &PyFloatKey64) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for PyFloatKey64 {}
+
+impl ::std::hash::Hash for PyFloatKey64 {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Returns the name `pydict_to_json`/`pydict_from_json` tag a `PyDictK` variant with in the
+/// `"key_type"` field of a serialized document. Keep in sync with [`PyDictK`]'s variant list.
+fn key_type_name(k_type: &PyDictK) -> &'static str {
     match *(k_type) {
-        PyDictK::I8 => {
-            Box::from_raw(dict as *mut PyDict<i8>);
+        PyDictK::U8 => "u8",
+        PyDictK::I8 => "i8",
+        PyDictK::I16 => "i16",
+        PyDictK::U16 => "u16",
+        PyDictK::I32 => "i32",
+        PyDictK::U32 => "u32",
+        PyDictK::I64 => "i64",
+        PyDictK::U64 => "u64",
+        PyDictK::PyBool => "bool",
+        PyDictK::PyString => "str",
+        PyDictK::F32 => "f32",
+        PyDictK::F64 => "f64",
+    }
+}
+
+trait JsonKey {
+    fn write_json(&self, out: &mut String);
+}
+
+macro_rules! impl_json_key_num {
+    ($($ty:ty),+) => {
+        $( impl JsonKey for $ty {
+            fn write_json(&self, out: &mut String) {
+                out.push_str(&self.to_string());
+            }
+        } )+
+    };
+}
+impl_json_key_num!(u8, i8, i16, u16, i32, u32, i64, u64);
+
+impl JsonKey for PyBool {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(if self.to_bool() { "true" } else { "false" });
+    }
+}
+
+impl JsonKey for PyString {
+    fn write_json(&self, out: &mut String) {
+        json_escape(out, &self.to_string());
+    }
+}
+
+impl JsonKey for PyFloatKey32 {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(&f32::from(*self).to_string());
+    }
+}
+
+impl JsonKey for PyFloatKey64 {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(&f64::from(*self).to_string());
+    }
+}
+
+fn json_escape(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
-        PyDictK::I16 => {
-            Box::from_raw(dict as *mut PyDict<i16>);
+    }
+    out.push('"');
+}
+
+/// Serializes a single value as a tagged `{"kind":..,"value":..}` node, so the resulting
+/// document stays self-describing without needing the original `PyArg` variant to read it
+/// back. Panics the whole process (via [`abort_and_exit`], like every other "expected X,
+/// found Y" mismatch in this module) on `PyTuple`/`PyList`/`PyDict` values: a `PyArg::PyDict`
+/// is a bare, key-type-erased pointer, so there is no way to recover the key type needed to
+/// serialize it back out. `PyBytes`/`PyBuffer`/`PyDate`/`PyTime`/`PyDateTime` values have no
+/// JSON node kind either and abort the same way.
+fn pyarg_to_json(out: &mut String, v: &PyArg) {
+    macro_rules! _num {
+        ($n:expr, $kind:expr) => {
+            out.push_str(&format!(r#"{{"kind":"{}","value":{}}}"#, $kind, $n))
+        };
+    }
+    match *v {
+        PyArg::I64(n) => _num!(n, "i64"),
+        PyArg::I32(n) => _num!(n, "i32"),
+        PyArg::I16(n) => _num!(n, "i16"),
+        PyArg::I8(n) => _num!(n, "i8"),
+        PyArg::U64(n) => _num!(n, "u64"),
+        PyArg::U32(n) => _num!(n, "u32"),
+        PyArg::U16(n) => _num!(n, "u16"),
+        PyArg::U8(n) => _num!(n, "u8"),
+        PyArg::F32(n) => _num!(n, "f32"),
+        PyArg::F64(n) => _num!(n, "f64"),
+        PyArg::PyBool(ref b) => {
+            out.push_str(&format!(r#"{{"kind":"bool","value":{}}}"#, b.to_bool()));
+        }
+        PyArg::PyString(ref s) => {
+            out.push_str(r#"{"kind":"str","value":"#);
+            json_escape(out, &s.to_string());
+            out.push('}');
+        }
+        PyArg::None => out.push_str(r#"{"kind":"none"}"#),
+        PyArg::PyTuple(_) | PyArg::PyList(_) | PyArg::PyDict(_) => {
+            abort_and_exit("nested containers are not supported as PyDict JSON values")
+        }
+        PyArg::PyBytes(_)
+        | PyArg::PyBuffer(_)
+        | PyArg::PyDate(_)
+        | PyArg::PyTime(_)
+        | PyArg::PyDateTime(_) => abort_and_exit("value type not supported as a PyDict JSON value"),
+    }
+}
+
+/// Parses a `{"kind":..,"value":..}` node produced by [`pyarg_to_json`] back into a `PyArg`.
+/// Returns `None` (rather than aborting) on a malformed node, since an untrusted document
+/// handed to [`pydict_from_json`] should be rejected, not crash the process.
+fn json_to_pyarg(node: &Json) -> Option<PyArg> {
+    let obj = node.as_obj()?;
+    let kind = Json::get(obj, "kind")?.as_str()?;
+    if kind == "none" {
+        return Some(PyArg::None);
+    }
+    let value = Json::get(obj, "value")?;
+    match kind {
+        "i64" => Some(PyArg::I64(value.as_i128()? as i64)),
+        "i32" => Some(PyArg::I32(value.as_i128()? as i32)),
+        "i16" => Some(PyArg::I16(value.as_i128()? as i16)),
+        "i8" => Some(PyArg::I8(value.as_i128()? as i8)),
+        "u64" => Some(PyArg::U64(value.as_i128()? as u64)),
+        "u32" => Some(PyArg::U32(value.as_i128()? as u32)),
+        "u16" => Some(PyArg::U16(value.as_i128()? as u16)),
+        "u8" => Some(PyArg::U8(value.as_i128()? as u8)),
+        "f32" => Some(PyArg::F32(value.as_f64()? as f32)),
+        "f64" => Some(PyArg::F64(value.as_f64()?)),
+        "bool" => Some(PyArg::PyBool(PyBool::from(value.as_bool()?))),
+        "str" => Some(PyArg::PyString(PyString::from(value.as_str()?.to_string()))),
+        _ => None,
+    }
+}
+
+/// A minimal JSON value, just enough to parse documents shaped like the ones
+/// [`pydict_to_json`] emits. Not a general-purpose parser.
+enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    /// A number parsed as a plain integer literal, kept as an `i128` instead of going through
+    /// `f64` so values outside f64's 53-bit mantissa - a `u64` near its max, for instance -
+    /// don't lose precision on the way back to a `PyArg`.
+    Int(i128),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::Str(ref s) => Some(s),
+            _ => None,
         }
-        PyDictK::I32 => {
-            Box::from_raw(dict as *mut PyDict<i32>);
+    }
+    fn as_obj(&self) -> Option<&[(String, Json)]> {
+        match *self {
+            Json::Obj(ref o) => Some(o),
+            _ => None,
         }
-        PyDictK::I64 => {
-            Box::from_raw(dict as *mut PyDict<i64>);
+    }
+    fn as_arr(&self) -> Option<&[Json]> {
+        match *self {
+            Json::Arr(ref a) => Some(a),
+            _ => None,
         }
-        PyDictK::U8 => {
-            Box::from_raw(dict as *mut PyDict<u8>);
+    }
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Num(n) => Some(n),
+            Json::Int(n) => Some(n as f64),
+            _ => None,
         }
-        PyDictK::U16 => {
-            Box::from_raw(dict as *mut PyDict<u16>);
+    }
+    fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Json::Int(n) => Some(n),
+            Json::Num(n) => Some(n as i128),
+            _ => None,
         }
-        PyDictK::U32 => {
-            Box::from_raw(dict as *mut PyDict<u16>);
+    }
+    fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(b) => Some(b),
+            _ => None,
         }
-        PyDictK::U64 => {
-            Box::from_raw(dict as *mut PyDict<u16>);
+    }
+    fn get<'a>(obj: &'a [(String, Json)], key: &str) -> Option<&'a Json> {
+        obj.iter().find(|entry| entry.0 == key).map(|entry| &entry.1)
+    }
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn new(s: &str) -> JsonParser {
+        JsonParser {
+            chars: s.chars().collect(),
+            pos: 0,
         }
-        PyDictK::PyString => {
-            Box::from_raw(dict as *mut PyDict<PyString>);
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
         }
-        PyDictK::PyBool => {
-            Box::from_raw(dict as *mut PyDict<PyBool>);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn expect_lit(&mut self, lit: &str) -> Option<()> {
+        for c in lit.chars() {
+            self.expect(c)?;
+        }
+        Some(())
+    }
+
+    fn parse_value(&mut self) -> Option<Json> {
+        self.skip_ws();
+        match self.peek()? {
+            '{' => self.parse_obj(),
+            '[' => self.parse_arr(),
+            '"' => self.parse_str().map(Json::Str),
+            't' => {
+                self.expect_lit("true")?;
+                Some(Json::Bool(true))
+            }
+            'f' => {
+                self.expect_lit("false")?;
+                Some(Json::Bool(false))
+            }
+            'n' => {
+                self.expect_lit("null")?;
+                Some(Json::Null)
+            }
+            _ => self.parse_num(),
+        }
+    }
+
+    fn parse_obj(&mut self) -> Option<Json> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Some(Json::Obj(entries));
+        }
+        loop {
+            self.skip_ws();
+            let key = self.parse_str()?;
+            self.skip_ws();
+            self.expect(':')?;
+            let val = self.parse_value()?;
+            entries.push((key, val));
+            self.skip_ws();
+            match self.peek()? {
+                ',' => self.pos += 1,
+                '}' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        Some(Json::Obj(entries))
+    }
+
+    fn parse_arr(&mut self) -> Option<Json> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Some(Json::Arr(items));
+        }
+        loop {
+            let val = self.parse_value()?;
+            items.push(val);
+            self.skip_ws();
+            match self.peek()? {
+                ',' => self.pos += 1,
+                ']' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        Some(Json::Arr(items))
+    }
+
+    fn parse_str(&mut self) -> Option<String> {
+        self.skip_ws();
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            let c = self.peek()?;
+            self.pos += 1;
+            match c {
+                '"' => break,
+                '\\' => {
+                    let esc = self.peek()?;
+                    self.pos += 1;
+                    match esc {
+                        '"' => out.push('"'),
+                        '\\' => out.push('\\'),
+                        '/' => out.push('/'),
+                        'n' => out.push('\n'),
+                        'r' => out.push('\r'),
+                        't' => out.push('\t'),
+                        'u' => {
+                            let hex: String = (0..4).filter_map(|_| {
+                                let c = self.peek()?;
+                                self.pos += 1;
+                                Some(c)
+                            }).collect();
+                            if hex.len() != 4 {
+                                return None;
+                            }
+                            let cp = u32::from_str_radix(&hex, 16).ok()?;
+                            out.push(::std::char::from_u32(cp)?);
+                        }
+                        _ => return None,
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        Some(out)
+    }
+
+    fn parse_num(&mut self) -> Option<Json> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || c == '+' || c == '-' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let s: String = self.chars[start..self.pos].iter().collect();
+        // A plain integer literal (no '.'/exponent) is parsed as an i128 first, so a value
+        // outside f64's 53-bit mantissa - a u64 near its max, say - round-trips exactly instead
+        // of losing precision through a float. Anything else (actual floats, or an integer too
+        // big even for i128) falls back to f64 as before.
+        if !s.contains('.') && !s.contains('e') && !s.contains('E') {
+            if let Ok(i) = s.parse::<i128>() {
+                return Some(Json::Int(i));
+            }
         }
+        s.parse::<f64>().ok().map(Json::Num)
     }
 }
 
-/// Types allowed as PyDict key values.
-pub enum PyDictK {
-    I64,
-    I32,
-    I16,
-    I8,
-    U64,
-    U32,
-    U16,
-    U8,
-    PyBool,
-    PyString,
+/// Serializes the type-erased dict into a tagged, self-describing JSON document, following
+/// the approach the rustdoc JSON backend uses of emitting every node with an explicit `kind`
+/// tag alongside its payload: `{"kind":"dict","key_type":"i64","entries":[[key,value],...]}`.
+/// Entry values are themselves tagged nodes (see [`pyarg_to_json`]), so the document carries
+/// enough information to reconstruct the original `PyArg` variant without a schema.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydict_to_json(dict: *mut size_t, k_type: &PyDictK) -> *mut PyString {
+    macro_rules! _to_json {
+        ($K:ty) => {{
+            let dict = &*(dict as *mut PyDict<$K>);
+            let mut entries = String::new();
+            for (k, v) in dict._inner.iter() {
+                if !entries.is_empty() {
+                    entries.push(',');
+                }
+                entries.push('[');
+                k.write_json(&mut entries);
+                entries.push(',');
+                pyarg_to_json(&mut entries, v);
+                entries.push(']');
+            }
+            entries
+        }};
+    }
+    let entries = match *(k_type) {
+        PyDictK::U8 => _to_json!(u8),
+        PyDictK::I8 => _to_json!(i8),
+        PyDictK::I16 => _to_json!(i16),
+        PyDictK::U16 => _to_json!(u16),
+        PyDictK::I32 => _to_json!(i32),
+        PyDictK::U32 => _to_json!(u32),
+        PyDictK::I64 => _to_json!(i64),
+        PyDictK::U64 => _to_json!(u64),
+        PyDictK::PyBool => _to_json!(PyBool),
+        PyDictK::PyString => _to_json!(PyString),
+        PyDictK::F32 => _to_json!(PyFloatKey32),
+        PyDictK::F64 => _to_json!(PyFloatKey64),
+    };
+    let doc = format!(
+        r#"{{"kind":"dict","key_type":"{}","entries":[{}]}}"#,
+        key_type_name(k_type),
+        entries
+    );
+    PyString::from(doc).into_raw()
+}
+
+/// Reconstructs a type-erased dict from a document previously produced by
+/// [`pydict_to_json`]. The embedded `"key_type"` tag is validated against `k_type` (using the
+/// same name table `pydict_get_key_type`'s discriminants map to) before any entry is parsed,
+/// so a mismatched or hand-edited document is rejected outright instead of being
+/// reinterpreted under the wrong key type. Returns a null pointer on any parse failure,
+/// `key_type` mismatch, or malformed entry.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydict_from_json(json: *mut PyString, k_type: &PyDictK) -> *mut size_t {
+    let json = PyString::from_ptr_to_string(json);
+    let doc = match JsonParser::new(&json).parse_value() {
+        Some(doc) => doc,
+        None => return ptr::null_mut(),
+    };
+    let obj = match doc.as_obj() {
+        Some(obj) => obj,
+        None => return ptr::null_mut(),
+    };
+    if Json::get(obj, "kind").and_then(Json::as_str) != Some("dict") {
+        return ptr::null_mut();
+    }
+    if Json::get(obj, "key_type").and_then(Json::as_str) != Some(key_type_name(k_type)) {
+        return ptr::null_mut();
+    }
+    let entries = match Json::get(obj, "entries").and_then(Json::as_arr) {
+        Some(e) => e,
+        None => return ptr::null_mut(),
+    };
+
+    macro_rules! _from_json {
+        ($K:ty; $key_from_json:expr) => {{
+            let mut dict: PyDict<$K> = PyDict::new();
+            for entry in entries {
+                let pair = match entry.as_arr() {
+                    Some(p) if p.len() == 2 => p,
+                    _ => return ptr::null_mut(),
+                };
+                let key = match $key_from_json(&pair[0]) {
+                    Some(k) => k,
+                    None => return ptr::null_mut(),
+                };
+                let value = match json_to_pyarg(&pair[1]) {
+                    Some(v) => v,
+                    None => return ptr::null_mut(),
+                };
+                dict._inner.insert(key, value);
+            }
+            dict.into_raw()
+        }};
+    }
+    match *(k_type) {
+        PyDictK::U8 => _from_json!(u8; |j: &Json| j.as_i128().map(|n| n as u8)),
+        PyDictK::I8 => _from_json!(i8; |j: &Json| j.as_i128().map(|n| n as i8)),
+        PyDictK::I16 => _from_json!(i16; |j: &Json| j.as_i128().map(|n| n as i16)),
+        PyDictK::U16 => _from_json!(u16; |j: &Json| j.as_i128().map(|n| n as u16)),
+        PyDictK::I32 => _from_json!(i32; |j: &Json| j.as_i128().map(|n| n as i32)),
+        PyDictK::U32 => _from_json!(u32; |j: &Json| j.as_i128().map(|n| n as u32)),
+        PyDictK::I64 => _from_json!(i64; |j: &Json| j.as_i128().map(|n| n as i64)),
+        PyDictK::U64 => _from_json!(u64; |j: &Json| j.as_i128().map(|n| n as u64)),
+        PyDictK::PyBool => _from_json!(PyBool; |j: &Json| j.as_bool().map(PyBool::from)),
+        PyDictK::PyString => {
+            _from_json!(PyString; |j: &Json| j.as_str().map(|s| PyString::from(s.to_string())))
+        }
+        PyDictK::F32 => {
+            _from_json!(PyFloatKey32; |j: &Json| j.as_f64().map(|n| PyFloatKey32::from(n as f32)))
+        }
+        PyDictK::F64 => _from_json!(PyFloatKey64; |j: &Json| j.as_f64().map(PyFloatKey64::from)),
+    }
 }
 
-pub(crate) mod key_bound {
-    use crate::pytypes::pybool::PyBool;
-    use crate::pytypes::pystring::PyString;
+fn same_key_type(a: &PyDictK, b: &PyDictK) -> bool {
+    ::std::mem::discriminant(a) == ::std::mem::discriminant(b)
+}
 
-    pub trait PyDictKey {}
-    impl PyDictKey for i64 {}
-    impl PyDictKey for i32 {}
-    impl PyDictKey for i16 {}
-    impl PyDictKey for i8 {}
-    impl PyDictKey for u64 {}
-    impl PyDictKey for u32 {}
-    impl PyDictKey for u16 {}
-    impl PyDictKey for u8 {}
-    impl PyDictKey for PyString {}
-    impl PyDictKey for PyBool {}
+/// Structurally compares two type-erased dicts. Mirrors the `impl_partialeq`-style generated
+/// match bindgen produces: one arm per key type that downcasts both pointers to the matching
+/// `PyDict<T>` and compares them for real, instead of pretending two incompatible layouts
+/// could ever be equal.
+///
+/// Returns `1` if equal, `0` if not equal, or `-1` if `k_a` and `k_b` name different key
+/// types (the two dicts can't be compared at all in that case).
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pydict_eq(
+    a: *mut size_t,
+    k_a: &PyDictK,
+    b: *mut size_t,
+    k_b: &PyDictK,
+) -> c_int {
+    if !same_key_type(k_a, k_b) {
+        return -1;
+    }
+    macro_rules! _eq {
+        ($K:ty) => {{
+            let a = &*(a as *mut PyDict<$K>);
+            let b = &*(b as *mut PyDict<$K>);
+            if a == b {
+                1
+            } else {
+                0
+            }
+        }};
+    }
+    match *(k_a) {
+        PyDictK::U8 => _eq!(u8),
+        PyDictK::I8 => _eq!(i8),
+        PyDictK::I16 => _eq!(i16),
+        PyDictK::U16 => _eq!(u16),
+        PyDictK::I32 => _eq!(i32),
+        PyDictK::U32 => _eq!(u32),
+        PyDictK::I64 => _eq!(i64),
+        PyDictK::U64 => _eq!(u64),
+        PyDictK::PyBool => _eq!(PyBool),
+        PyDictK::PyString => _eq!(PyString),
+        PyDictK::F32 => _eq!(PyFloatKey32),
+        PyDictK::F64 => _eq!(PyFloatKey64),
+    }
 }
 
+/// Produces a `Debug`-style string of a type-erased dict, downcasting to the matching
+/// `PyDict<T>` first so the real contents (not just the opaque pointer) are shown. Useful for
+/// logging and debugging across the FFI boundary, where the Rust side only ever sees a
+/// `*mut size_t` plus a `PyDictK`.
 #[doc(hidden)]
 #[no_mangle]
-pub extern "C" fn pydict_get_key_type(k: u32) -> *mut PyDictK {
-    match k {
-        1 => Box::into_raw(Box::new(PyDictK::U8)),
-        2 => Box::into_raw(Box::new(PyDictK::I8)),
-        3 => Box::into_raw(Box::new(PyDictK::I16)),
-        4 => Box::into_raw(Box::new(PyDictK::U16)),
-        5 => Box::into_raw(Box::new(PyDictK::I32)),
-        6 => Box::into_raw(Box::new(PyDictK::U32)),
-        7 => Box::into_raw(Box::new(PyDictK::I64)),
-        8 => Box::into_raw(Box::new(PyDictK::U64)),
-        11 => Box::into_raw(Box::new(PyDictK::PyBool)),
-        12 => Box::into_raw(Box::new(PyDictK::PyString)),
-        _ => abort_and_exit("type not supported as PyDict key type"),
+pub unsafe extern "C" fn pydict_debug(dict: *mut size_t, k_type: &PyDictK) -> *mut PyString {
+    macro_rules! _debug {
+        ($K:ty) => {{
+            let dict = &*(dict as *mut PyDict<$K>);
+            format!("{:?}", dict)
+        }};
+    }
+    let s = match *(k_type) {
+        PyDictK::U8 => _debug!(u8),
+        PyDictK::I8 => _debug!(i8),
+        PyDictK::I16 => _debug!(i16),
+        PyDictK::U16 => _debug!(u16),
+        PyDictK::I32 => _debug!(i32),
+        PyDictK::U32 => _debug!(u32),
+        PyDictK::I64 => _debug!(i64),
+        PyDictK::U64 => _debug!(u64),
+        PyDictK::PyBool => _debug!(PyBool),
+        PyDictK::PyString => _debug!(PyString),
+        PyDictK::F32 => _debug!(PyFloatKey32),
+        PyDictK::F64 => _debug!(PyFloatKey64),
+    };
+    PyString::from(s).into_raw()
+}
+
+#[test]
+fn get_item_returns_owned_value() {
+    unsafe {
+        let mut hm = HashMap::new();
+        hm.insert(0u16, PyArg::PyString(PyString::from("zero")));
+        let dict = PyDict::from_iter(hm).into_raw() as *mut size_t;
+        let k_type = PyDictK::U16;
+
+        let found_key = Box::into_raw(Box::new(PyArg::U16(0)));
+        let mut out: *mut PyArg = ptr::null_mut();
+        assert_eq!(pydict_get_item(dict, &k_type, found_key, &mut out), 1);
+        assert!(!out.is_null());
+        match *Box::from_raw(out) {
+            PyArg::PyString(ref val) => assert_eq!(val, &PyString::from("zero")),
+            _ => panic!(),
+        }
+
+        let missing_key = Box::into_raw(Box::new(PyArg::U16(1)));
+        let mut missing_out: *mut PyArg = ptr::null_mut();
+        assert_eq!(
+            pydict_get_item(dict, &k_type, missing_key, &mut missing_out),
+            0
+        );
+        assert!(missing_out.is_null());
+
+        pydict_free(dict, &k_type);
+    }
+}
+
+#[test]
+fn roundtrip_every_key_type() {
+    // every discriminant declared in `define_pydict_keys!` must allocate and free the
+    // matching `PyDict<K>` monomorphization; a mismatched `Box::from_raw` would either
+    // leak, double-free, or corrupt memory, so this exercises all of them.
+    for disc in &[1u32, 2, 3, 4, 5, 6, 7, 8, 11, 12, 13, 14] {
+        unsafe {
+            let k_type = pydict_get_key_type(*disc);
+            let dict = pydict_new(&*k_type);
+            assert!(!dict.is_null());
+            pydict_free(dict, &*k_type);
+            Box::from_raw(k_type);
+        }
+    }
+}
+
+#[test]
+fn float_keys_normalize_zero_and_nan() {
+    // `+0.0`/`-0.0` must hash and compare equal, and every `NaN` bit pattern must collapse
+    // to the same key, mirroring how Python treats these values as dict keys.
+    assert_eq!(PyFloatKey32::from(0.0f32), PyFloatKey32::from(-0.0f32));
+    assert_eq!(
+        PyFloatKey32::from(f32::NAN),
+        PyFloatKey32::from(-f32::NAN)
+    );
+    assert_eq!(PyFloatKey64::from(0.0f64), PyFloatKey64::from(-0.0f64));
+    assert_eq!(
+        PyFloatKey64::from(f64::NAN),
+        PyFloatKey64::from(-f64::NAN)
+    );
+
+    unsafe {
+        let k_type = PyDictK::F64;
+        let dict = pydict_new(&k_type);
+        let key = Box::into_raw(Box::new(PyArg::F64(0.0)));
+        let value = Box::into_raw(Box::new(PyArg::PyString(PyString::from("zero"))));
+        pydict_insert(dict, &k_type, key, value);
+
+        let lookup = Box::into_raw(Box::new(PyArg::F64(-0.0)));
+        let mut out: *mut PyArg = ptr::null_mut();
+        assert_eq!(pydict_get_item(dict, &k_type, lookup, &mut out), 1);
+        assert!(!out.is_null());
+        match *Box::from_raw(out) {
+            PyArg::PyString(ref val) => assert_eq!(val, &PyString::from("zero")),
+            _ => panic!(),
+        }
+        pydict_free(dict, &k_type);
+    }
+}
+
+#[test]
+fn json_roundtrip() {
+    unsafe {
+        let k_type = PyDictK::I64;
+        let dict = pydict_new(&k_type);
+        let key = Box::into_raw(Box::new(PyArg::I64(7)));
+        let value = Box::into_raw(Box::new(PyArg::PyString(PyString::from("seven"))));
+        pydict_insert(dict, &k_type, key, value);
+
+        let json = pydict_to_json(dict, &k_type);
+        let json_str = PyString::from_ptr_to_string(json);
+        assert_eq!(
+            json_str,
+            r#"{"kind":"dict","key_type":"i64","entries":[[7,{"kind":"str","value":"seven"}]]}"#
+        );
+
+        let restored = pydict_from_json(PyString::from(json_str).into_raw(), &k_type);
+        assert!(!restored.is_null());
+        let lookup = Box::into_raw(Box::new(PyArg::I64(7)));
+        let mut out: *mut PyArg = ptr::null_mut();
+        assert_eq!(pydict_get_item(restored, &k_type, lookup, &mut out), 1);
+        match *Box::from_raw(out) {
+            PyArg::PyString(ref val) => assert_eq!(val, &PyString::from("seven")),
+            _ => panic!(),
+        }
+        pydict_free(dict, &k_type);
+        pydict_free(restored, &k_type);
+
+        // wrong key_type tag must be rejected rather than reinterpreted
+        let bad_k_type = PyDictK::U8;
+        let wrong = pydict_from_json(
+            PyString::from(r#"{"kind":"dict","key_type":"i64","entries":[]}"#.to_string())
+                .into_raw(),
+            &bad_k_type,
+        );
+        assert!(wrong.is_null());
+    }
+}
+
+#[test]
+fn json_roundtrip_preserves_wide_integers() {
+    // A value outside f64's 53-bit mantissa must come back exactly, not rounded through a
+    // float: u64::max_value() in particular would wrap to -1 if it were ever cast via f64/i64.
+    unsafe {
+        let k_type = PyDictK::I64;
+        let dict = pydict_new(&k_type);
+        let key = Box::into_raw(Box::new(PyArg::I64(1)));
+        let value = Box::into_raw(Box::new(PyArg::U64(u64::max_value())));
+        pydict_insert(dict, &k_type, key, value);
+
+        let json = pydict_to_json(dict, &k_type);
+        let json_str = PyString::from_ptr_to_string(json);
+        let restored = pydict_from_json(PyString::from(json_str).into_raw(), &k_type);
+        assert!(!restored.is_null());
+
+        let lookup = Box::into_raw(Box::new(PyArg::I64(1)));
+        let mut out: *mut PyArg = ptr::null_mut();
+        assert_eq!(pydict_get_item(restored, &k_type, lookup, &mut out), 1);
+        assert_eq!(*Box::from_raw(out), PyArg::U64(u64::max_value()));
+
+        pydict_free(dict, &k_type);
+        pydict_free(restored, &k_type);
+    }
+}
+
+#[test]
+fn eq_and_debug_across_type_erased_dicts() {
+    unsafe {
+        let k_type = PyDictK::I32;
+        let a = pydict_new(&k_type);
+        let b = pydict_new(&k_type);
+        pydict_insert(
+            a,
+            &k_type,
+            Box::into_raw(Box::new(PyArg::I32(1))),
+            Box::into_raw(Box::new(PyArg::I32(2))),
+        );
+        pydict_insert(
+            b,
+            &k_type,
+            Box::into_raw(Box::new(PyArg::I32(1))),
+            Box::into_raw(Box::new(PyArg::I32(2))),
+        );
+        assert_eq!(pydict_eq(a, &k_type, b, &k_type), 1);
+
+        pydict_insert(
+            b,
+            &k_type,
+            Box::into_raw(Box::new(PyArg::I32(1))),
+            Box::into_raw(Box::new(PyArg::I32(3))),
+        );
+        assert_eq!(pydict_eq(a, &k_type, b, &k_type), 0);
+
+        let other_k_type = PyDictK::U8;
+        assert_eq!(pydict_eq(a, &k_type, b, &other_k_type), -1);
+
+        let debug_str = PyString::from_ptr_to_string(pydict_debug(a, &k_type));
+        assert!(debug_str.contains("I32"));
+
+        pydict_free(a, &k_type);
+        pydict_free(b, &k_type);
     }
 }
@@ -20,18 +20,24 @@
 //! // convert from raw pointer to an owned String
 //! let rust_string = unsafe { PyString::from_ptr_to_string(ptr) };
 //! ```
-use libc::c_char;
-use std::ffi::CString;
+use libc::{c_char, size_t};
+use std::ffi::{CStr, CString};
 
-use std::convert::From;
+use std::convert::{From, TryFrom};
 use std::fmt;
+use std::slice;
+use std::str;
 
 /// An analog of a Python string.
 ///
+/// Stores its contents in a plain `Vec<u8>` rather than a `CString`, so a Python `str`
+/// containing an embedded NUL byte round-trips without panicking; UTF-8 validity is only
+/// checked on demand, by [`to_str`](PyString::to_str) or the fallible [`try_from`] constructor.
+///
 /// Read the [module docs](index.html) for more information.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PyString {
-    _inner: CString,
+    _inner: Vec<u8>,
 }
 
 impl PyString {
@@ -40,26 +46,42 @@ impl PyString {
         *Box::from_raw(ptr)
     }
     /// Constructs an owned String from a raw pointer.
+    ///
+    /// # Panics
+    /// Panics if the underlying bytes are not valid UTF-8.
     pub unsafe fn from_ptr_to_string(ptr: *mut PyString) -> String {
         let pystr = *(Box::from_raw(ptr));
-        String::from(pystr._inner.to_str().unwrap())
+        pystr.to_str().unwrap().to_owned()
     }
     /// Returns PyString as a raw pointer. Use this whenever you want to return
     /// a PyString to Python.
     pub fn into_raw(self) -> *mut PyString {
         Box::into_raw(Box::new(self))
     }
-    /// Return a PyString from a raw char pointer.
+    /// Return a PyString from a raw, NUL-terminated char pointer. Any bytes past the first
+    /// NUL are lost; use [`from_raw_with_len`](PyString::from_raw_with_len) when the source
+    /// may contain interior NULs.
     pub unsafe fn from_raw(ptr: *const c_char) -> PyString {
         PyString {
-            _inner: CStr::from_ptr(ptr).to_owned(),
+            _inner: CStr::from_ptr(ptr).to_bytes().to_vec(),
         }
     }
+    /// Copies `len` bytes starting at `ptr` into an owned PyString, preserving any interior
+    /// NUL bytes instead of stopping at the first one.
+    pub unsafe fn from_raw_with_len(ptr: *const c_char, len: size_t) -> PyString {
+        PyString {
+            _inner: slice::from_raw_parts(ptr as *const u8, len).to_vec(),
+        }
+    }
+    /// Validates the underlying bytes as UTF-8 and returns them as a `&str`.
+    pub fn to_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(&self._inner)
+    }
 }
 
 impl fmt::Display for PyString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", String::from(self._inner.to_str().unwrap()))
+        write!(f, "{}", String::from_utf8_lossy(&self._inner))
     }
 }
 
@@ -67,7 +89,7 @@ impl<'a> From<&'a str> for PyString {
     /// Copies a string slice to a PyString.
     fn from(s: &'a str) -> PyString {
         PyString {
-            _inner: CString::new(s).unwrap(),
+            _inner: s.as_bytes().to_vec(),
         }
     }
 }
@@ -76,11 +98,23 @@ impl From<String> for PyString {
     /// Copies a String to a PyString.
     fn from(s: String) -> PyString {
         PyString {
-            _inner: CString::new(s).unwrap(),
+            _inner: s.into_bytes(),
         }
     }
 }
 
+impl<'a> TryFrom<&'a [u8]> for PyString {
+    type Error = str::Utf8Error;
+    /// Validates `bytes` as UTF-8 before copying them into a PyString, so the caller can
+    /// detect invalid input instead of panicking.
+    fn try_from(bytes: &'a [u8]) -> Result<PyString, str::Utf8Error> {
+        str::from_utf8(bytes)?;
+        Ok(PyString {
+            _inner: bytes.to_vec(),
+        })
+    }
+}
+
 impl From<PyString> for String {
     fn from(s: PyString) -> String {
         s.to_string()
@@ -97,24 +131,35 @@ pub unsafe extern "C" fn pystring_free(ptr: *mut PyString) {
     Box::from_raw(ptr);
 }
 
-use std::ffi::CStr;
-/// Creates a PyString wrapper from a raw c_char pointer
+/// Creates a PyString wrapper from a raw, NUL-terminated c_char pointer.
 #[doc(hidden)]
 #[no_mangle]
 pub unsafe extern "C" fn pystring_new(ptr: *const c_char) -> *mut PyString {
-    let pystr = PyString {
-        _inner: CStr::from_ptr(ptr).to_owned(),
-    };
-    pystr.into_raw()
+    PyString::from_raw(ptr).into_raw()
+}
+
+/// Creates a PyString wrapper from a raw c_char pointer and an explicit length, so interior
+/// NUL bytes in the source string survive the round trip instead of truncating at the first
+/// one like [`pystring_new`] does.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pystring_new_with_len(ptr: *const c_char, len: size_t) -> *mut PyString {
+    PyString::from_raw_with_len(ptr, len).into_raw()
 }
 
-/// Consumes the wrapper and returns a raw c_char pointer. Afterwards is not necessary
-/// to destruct it as it has already been consumed.
+/// Consumes the wrapper and returns a raw, NUL-terminated c_char pointer. Afterwards is not
+/// necessary to destruct it as it has already been consumed.
+///
+/// If the string contains an interior NUL byte it can't be represented as a C string; an
+/// empty string is returned in that case rather than panicking.
 #[doc(hidden)]
 #[no_mangle]
 pub unsafe extern "C" fn pystring_get_str(ptr: *mut PyString) -> *const c_char {
     let pystr: PyString = PyString::from_ptr(ptr);
-    pystr._inner.into_raw()
+    match CString::new(pystr._inner) {
+        Ok(cstr) => cstr.into_raw(),
+        Err(_) => CString::new("").unwrap().into_raw(),
+    }
 }
 
 #[cfg(test)]
@@ -131,4 +176,17 @@ mod tests {
             String::from(source);
         }
     }
+
+    #[test]
+    fn survives_embedded_nul_bytes() {
+        let source = "before\0after";
+        let pystr = PyString::from(source);
+        assert_eq!(pystr.to_str().unwrap(), source);
+    }
+
+    #[test]
+    fn try_from_rejects_invalid_utf8() {
+        let invalid: Vec<u8> = vec![0x00, 0x9f, 0x92];
+        assert!(PyString::try_from(invalid.as_slice()).is_err());
+    }
 }
@@ -14,8 +14,9 @@ extern crate cpython;
 extern crate syn;
 extern crate libc;
 extern crate walkdir;
+extern crate serde_cbor;
 
-use std::io::Read;
+use std::io::{Read, Write};
 use std::fs::File;
 use std::path::Path;
 use std::ptr;
@@ -26,8 +27,11 @@ pub mod pytypes;
 
 // re-export
 pub use self::pytypes::pybool::PyBool;
+pub use self::pytypes::pybuffer::PyBuffer;
+pub use self::pytypes::pycell::{PyBorrowError, PyCell, PyRef, PyRefMut};
 pub use self::pytypes::pystring::PyString;
 pub use self::pytypes::pylist::PyList;
+pub use self::pytypes::pyset::{PySet, PyFrozenSet};
 pub use self::pytypes::pydict::PyDict;
 pub use self::pytypes::pytuple::PyTuple;
 pub use self::pytypes::PyArg;
@@ -56,6 +60,7 @@ pub extern "C" fn parse_src(path: *mut PyString, krate_data: &mut KrateData) ->
             return err;
         }
     }
+    krate_data.finalize();
     return ptr::null_mut::<PyString>();
 }
 
@@ -73,46 +78,120 @@ fn parse_file(krate_data: &mut KrateData, path: &Path) -> Result<(), *mut PyStri
                                           path.to_str().unwrap()))
                            .as_ptr());
     }
-    match syn::parse_crate(&src) {
+    parse_str(krate_data, &src)
+}
+
+fn parse_str(krate_data: &mut KrateData, src: &str) -> Result<(), *mut PyString> {
+    match syn::parse_crate(src) {
         Ok(krate) => {
             syn::visit::walk_crate(krate_data, &krate);
-            krate_data.collect_values();
+            Ok(())
         }
-        Err(err) => return Err(PyString::from(err).as_ptr()),
-    };
-    Ok(())
+        Err(err) => Err(PyString::from(err).as_ptr()),
+    }
 }
 
+/// Parses the output of `rustc -Zunpretty=expanded`/`--pretty=expanded` for a crate and merges
+/// the result into `krate_data`, exactly like `parse_src` does for raw sources. Unlike `parse_src`
+/// this takes the expanded text directly rather than a path to walk, since macro expansion
+/// collapses the whole crate into a single compiler-emitted blob: it's how a `pub extern "C"`
+/// function that only exists after a `macro_rules!` expansion (including rustypy's own
+/// declarative macros, or a user's) becomes visible to `KrateData`. The Python driver can call
+/// this alongside or instead of `parse_src` when a crate relies on macros to define its exported
+/// surface.
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn parse_expanded_src(src: *mut PyString, krate_data: &mut KrateData) -> *mut PyString {
+    let src = unsafe { PyString::from_ptr_to_string(src) };
+    match parse_str(krate_data, &src) {
+        Ok(()) => {
+            krate_data.finalize();
+            ptr::null_mut::<PyString>()
+        }
+        Err(err) => err,
+    }
+}
+
+/// A sentinel entry in the `prefixes` list passed to [`krate_data_new`]/[`KrateData::new`] that
+/// opts a crate out of the default sorted-by-`(module, name)` binding order (see
+/// [`KrateData::finalize`]) and back into raw declaration order. Not a real prefix: stripped
+/// out of the list before it's used to filter function names.
+const UNORDERED_FLAG: &'static str = "#unordered";
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct KrateData {
     functions: Vec<FnDef>,
+    structs: Vec<StructDef>,
     collected: Vec<String>,
+    stubs: Vec<String>,
     prefixes: Vec<String>,
+    sorted: bool,
+    mod_path: Vec<String>,
 }
 
 impl KrateData {
-    fn new(prefixes: Vec<String>) -> KrateData {
+    /// `prefixes` filters which `pub fn`s are exported; it also doubles as the opt-out switch
+    /// for sorted output (see [`UNORDERED_FLAG`]), since adding a dedicated FFI constructor arg
+    /// would mean breaking every existing `krate_data_new` caller for a knob most users should
+    /// never need to touch.
+    fn new(mut prefixes: Vec<String>) -> KrateData {
+        let sorted = match prefixes.iter().position(|p| p == UNORDERED_FLAG) {
+            Some(pos) => {
+                prefixes.remove(pos);
+                false
+            }
+            None => true,
+        };
         KrateData {
             functions: vec![],
+            structs: vec![],
             collected: vec![],
+            stubs: vec![],
             prefixes: prefixes,
+            sorted: sorted,
+            mod_path: vec![],
         }
     }
 
+    /// Called once the whole crate has been walked (every file for `parse_src`, the single
+    /// expanded blob for `parse_expanded_src`), so that declaration order can be made
+    /// deterministic across machines and unrelated-file edits before anything is exposed through
+    /// `krate_data_iter`: regardless of which order `walkdir`/`syn::visit` happened to traverse
+    /// files and items in, the accumulated `functions` are stably sorted by `(module, name)`
+    /// unless [`UNORDERED_FLAG`] opted the crate out, then drained into `collected`/`stubs` by
+    /// `collect_values` as before.
+    fn finalize(&mut self) {
+        if self.sorted {
+            self.functions.sort_by(|a, b| (&a.module, &a.name).cmp(&(&b.module, &b.name)));
+        }
+        self.collect_values();
+    }
+
     fn collect_values(&mut self) {
+        let opaque: Vec<String> = self.structs.iter().map(|s| s.name.clone()).collect();
         let mut add = true;
         for v in self.functions.drain(..) {
             let FnDef {
                 name: mut fndef,
                 args,
                 output,
+                module: _,
             } = v;
+            let stub_name = fndef.clone();
+            let stub_args: Vec<String> = args.iter()
+                .enumerate()
+                .map(|(i, ty)| format!("arg{}: {}", i, pyi_repr(ty, &opaque)))
+                .collect();
+            let stub_ret = match output {
+                syn::FunctionRetTy::Default => "None".to_string(),
+                syn::FunctionRetTy::Ty(ref ty) => pyi_repr(ty, &opaque),
+            };
             if !args.is_empty() {
                 fndef.push_str("::");
                 args.iter()
                     .fold(&mut fndef, |mut acc, arg| {
-                        if let Ok(repr) = type_repr(arg, None) {
+                        if let Ok(repr) = type_repr(arg, None, &opaque) {
                             acc.push_str(&repr);
                             acc.push(';');
                         } else {
@@ -125,18 +204,45 @@ impl KrateData {
                 match output {
                     syn::FunctionRetTy::Default => fndef.push_str("type(void)"),
                     syn::FunctionRetTy::Ty(ty) => {
-                        if let Ok(ty) = type_repr(&ty, None) {
+                        if let Ok(ty) = type_repr(&ty, None, &opaque) {
                             fndef.push_str(&ty)
                         } else {
                             continue;
                         }
                     }
                 }
+                self.stubs
+                    .push(format!("def {}({}) -> {}: ...",
+                                   stub_name,
+                                   stub_args.join(", "),
+                                   stub_ret));
                 self.collected.push(fndef);
             } else {
                 add = true
             }
         }
+        for v in self.structs.drain(..) {
+            let StructDef { name, fields } = v;
+            let mut def = format!("struct({})", name);
+            if !fields.is_empty() {
+                def.push_str("::");
+                fields.iter()
+                    .fold(&mut def, |mut acc, field| {
+                        if let Ok(repr) = type_repr(field, None, &opaque) {
+                            acc.push_str(&repr);
+                            acc.push(';');
+                        } else {
+                            add = false;
+                        }
+                        acc
+                    });
+            }
+            if add {
+                self.collected.push(def);
+            } else {
+                add = true
+            }
+        }
     }
 
     fn add_fn(&mut self, name: String, fn_decl: &syn::FnDecl) {
@@ -156,12 +262,17 @@ impl KrateData {
                               name,
                               args: args,
                               output: output,
+                              module: self.mod_path.join("::"),
                           });
                 break;
             }
         }
     }
 
+    fn add_struct(&mut self, name: String, fields: Vec<syn::Ty>) {
+        self.structs.push(StructDef { name, fields });
+    }
+
     fn iter_krate(&self, idx: usize) -> Option<&str> {
         if self.collected.len() >= (idx + 1) {
             Some(&self.collected[idx])
@@ -171,19 +282,78 @@ impl KrateData {
     }
 }
 
-fn type_repr(ty: &syn::Ty, r: Option<&str>) -> Result<String, ()> {
+/// Resolves a `syn::Ty` to its wire representation. `opaque` is the set of public struct/enum
+/// names `KrateData` has already collected from this crate; a path type naming one of them
+/// resolves to `opaque(Name)` (an FFI handle the Python side treats as a pointer) instead of
+/// `type(Name)` (a value `KrateData` assumes the Python side can convert natively).
+///
+/// The full segment path is kept (joined with `::`) rather than just the last identifier, and
+/// any angle-bracketed generic arguments are resolved recursively and appended, so e.g.
+/// `std::rc::Rc<u32>` becomes `type(std::rc::Rc<type(u32)>)` instead of collapsing to
+/// `type(Rc)` with the element type thrown away — except for the handful of containers this
+/// crate's own FFI types mirror directly, which get their own keyword instead of the generic
+/// `<...>` syntax: `Vec<T>` becomes `type(list <inner>)` (matching `PyList`), `HashMap<K, V>`
+/// becomes `type(dict <k> <v>)` (matching `PyDict`), and `Option<T>`/`Box<T>` unwrap to just
+/// `T`'s own repr, since neither adds a shape the wire format needs to track. `Slice`/`Array`/
+/// `Tup` are resolved recursively too instead of failing the whole function they appear in.
+fn type_repr(ty: &syn::Ty, r: Option<&str>, opaque: &[String]) -> Result<String, ()> {
     let mut repr = String::new();
     match *ty {
         syn::Ty::Path(_, ref path) => {
             let syn::Path { ref segments, .. } = *path;
-            if let Some(ty) = segments.last() {
-                if r.is_some() {
-                    Ok(format!("type({} {})", r.unwrap(), ty.ident))
-                } else {
-                    Ok(format!("type({})", ty.ident))
+            if segments.is_empty() {
+                return Err(());
+            }
+            let base_name = segments.iter()
+                .map(|s| format!("{}", s.ident))
+                .collect::<Vec<_>>()
+                .join("::");
+            let last = segments.last().unwrap();
+            let generic_args = if let syn::PathParameters::AngleBracketed(ref data) =
+                last.parameters {
+                &data.types
+            } else {
+                return Err(());
+            };
+            let last_ident = format!("{}", last.ident);
+            match (last_ident.as_str(), generic_args.len()) {
+                ("Option", 1) | ("Box", 1) => return type_repr(&generic_args[0], r, opaque),
+                ("Vec", 1) => {
+                    let inner = type_repr(&generic_args[0], None, opaque)?;
+                    return Ok(match r {
+                        Some(r) => format!("type({} list {})", r, inner),
+                        None => format!("type(list {})", inner),
+                    });
+                }
+                ("HashMap", 2) => {
+                    let key = type_repr(&generic_args[0], None, opaque)?;
+                    let value = type_repr(&generic_args[1], None, opaque)?;
+                    return Ok(match r {
+                        Some(r) => format!("type({} dict {} {})", r, key, value),
+                        None => format!("type(dict {} {})", key, value),
+                    });
                 }
+                _ => {}
+            }
+            let mut full_name = base_name.clone();
+            if !generic_args.is_empty() {
+                let mut args = Vec::with_capacity(generic_args.len());
+                for t in generic_args {
+                    args.push(type_repr(t, None, opaque)?);
+                }
+                full_name.push('<');
+                full_name.push_str(&args.join(","));
+                full_name.push('>');
+            }
+            let kind = if opaque.iter().any(|o| o == &last_ident) {
+                "opaque"
             } else {
-                Err(())
+                "type"
+            };
+            if r.is_some() {
+                Ok(format!("{}({} {})", kind, r.unwrap(), full_name))
+            } else {
+                Ok(format!("{}({})", kind, full_name))
             }
         }
         syn::Ty::Ptr(ref ty) => {
@@ -195,7 +365,7 @@ fn type_repr(ty: &syn::Ty, r: Option<&str>) -> Result<String, ()> {
                 syn::Mutability::Immutable => "*const",
                 syn::Mutability::Mutable => "*mut",
             };
-            repr.push_str(&type_repr(&*ty, Some(m))?);
+            repr.push_str(&type_repr(&*ty, Some(m), opaque)?);
             Ok(repr)
         }
         syn::Ty::Rptr(_, ref ty) => {
@@ -207,13 +377,92 @@ fn type_repr(ty: &syn::Ty, r: Option<&str>) -> Result<String, ()> {
                 syn::Mutability::Immutable => "&",
                 syn::Mutability::Mutable => "&mut",
             };
-            repr.push_str(&type_repr(&*ty, Some(m))?);
+            repr.push_str(&type_repr(&*ty, Some(m), opaque)?);
             Ok(repr)
         }
+        syn::Ty::Slice(ref ty) => Ok(format!("type([{}])", type_repr(&*ty, None, opaque)?)),
+        syn::Ty::Array(ref ty, _) => Ok(format!("type([{}])", type_repr(&*ty, None, opaque)?)),
+        syn::Ty::Tup(ref elems) => {
+            let mut parts = Vec::with_capacity(elems.len());
+            for t in elems {
+                parts.push(type_repr(t, None, opaque)?);
+            }
+            Ok(format!("type(tuple; {})", parts.join("; ")))
+        }
         _ => Err(()),
     }
 }
 
+/// Resolves a `syn::Ty` to a PEP 484 type annotation for [`krate_data_emit_stubs`], on a
+/// best-effort basis: unlike [`type_repr`], an argument this can't map to a Python type falls
+/// back to `Any` instead of dropping the function it belongs to, since a `.pyi` stub only needs
+/// to be useful for editor autocompletion, not a complete or authoritative wire contract.
+/// Pointer/reference wrappers are transparent (Python has no equivalent to annotate), and
+/// `Option<T>`/`Box<T>` unwrap to `T` for the same reason `type_repr` unwraps them.
+fn pyi_repr(ty: &syn::Ty, opaque: &[String]) -> String {
+    match *ty {
+        syn::Ty::Path(_, ref path) => {
+            let segments = &path.segments;
+            let last = match segments.last() {
+                Some(last) => last,
+                None => return "Any".to_string(),
+            };
+            let ident = format!("{}", last.ident);
+            let empty = vec![];
+            let generic_args = if let syn::PathParameters::AngleBracketed(ref data) =
+                last.parameters {
+                &data.types
+            } else {
+                &empty
+            };
+            match (ident.as_str(), generic_args.len()) {
+                ("i8", 0) | ("i16", 0) | ("i32", 0) | ("i64", 0) | ("u8", 0) | ("u16", 0) |
+                ("u32", 0) | ("u64", 0) | ("isize", 0) | ("usize", 0) => "int".to_string(),
+                ("f32", 0) | ("f64", 0) => "float".to_string(),
+                ("bool", 0) | ("PyBool", 0) => "bool".to_string(),
+                ("str", 0) | ("String", 0) | ("PyString", 0) => "str".to_string(),
+                ("Option", 1) | ("Box", 1) => pyi_repr(&generic_args[0], opaque),
+                ("Vec", 1) | ("PyList", 1) => {
+                    format!("list[{}]", pyi_repr(&generic_args[0], opaque))
+                }
+                ("HashMap", 2) | ("PyDict", 2) => {
+                    format!("dict[{}, {}]",
+                            pyi_repr(&generic_args[0], opaque),
+                            pyi_repr(&generic_args[1], opaque))
+                }
+                ("PyTuple", _) => "tuple".to_string(),
+                _ => {
+                    if opaque.iter().any(|o| o == &ident) {
+                        ident
+                    } else {
+                        "Any".to_string()
+                    }
+                }
+            }
+        }
+        syn::Ty::Ptr(ref ty) => {
+            let syn::MutTy { ref ty, .. } = **ty;
+            pyi_repr(&*ty, opaque)
+        }
+        syn::Ty::Rptr(_, ref ty) => {
+            let syn::MutTy { ref ty, .. } = **ty;
+            pyi_repr(&*ty, opaque)
+        }
+        syn::Ty::Slice(ref ty) | syn::Ty::Array(ref ty, _) => {
+            format!("list[{}]", pyi_repr(&*ty, opaque))
+        }
+        syn::Ty::Tup(ref elems) => {
+            if elems.is_empty() {
+                "None".to_string()
+            } else {
+                let parts: Vec<String> = elems.iter().map(|t| pyi_repr(t, opaque)).collect();
+                format!("tuple[{}]", parts.join(", "))
+            }
+        }
+        _ => "Any".to_string(),
+    }
+}
+
 impl syn::visit::Visitor for KrateData {
     fn visit_item(&mut self, item: &syn::Item) {
         match item.node {
@@ -224,9 +473,27 @@ impl syn::visit::Visitor for KrateData {
                 }
             }
             syn::ItemKind::Mod(Some(ref items)) => {
+                self.mod_path.push(format!("{}", item.ident));
                 for item in items {
                     self.visit_item(item);
                 }
+                self.mod_path.pop();
+            }
+            syn::ItemKind::Struct(ref data, _) => {
+                if let syn::Visibility::Public = item.vis {
+                    let name = format!("{}", item.ident);
+                    let fields = data.fields().iter().map(|f| f.ty.clone()).collect();
+                    self.add_struct(name, fields);
+                }
+            }
+            syn::ItemKind::Enum(ref variants, _) => {
+                if let syn::Visibility::Public = item.vis {
+                    let name = format!("{}", item.ident);
+                    let fields = variants.iter()
+                        .flat_map(|v| v.data.fields().iter().map(|f| f.ty.clone()))
+                        .collect();
+                    self.add_struct(name, fields);
+                }
             }
             _ => {
                 /*
@@ -237,8 +504,6 @@ impl syn::visit::Visitor for KrateData {
                 Const(Box<Ty>, Box<Expr>),
                 ForeignMod(ForeignMod),
                 Ty(Box<Ty>, Generics),
-                Enum(Vec<Variant>, Generics),
-                Struct(VariantData, Generics),
                 Union(VariantData, Generics),
                 Trait(Unsafety, Generics, Vec<TyParamBound>, Vec<TraitItem>),
                 DefaultImpl(Unsafety, Path),
@@ -255,6 +520,16 @@ struct FnDef {
     name: String,
     output: syn::FunctionRetTy,
     args: Vec<syn::Ty>,
+    /// Dotted module path this function was declared under, e.g. `"foo::bar"` for an `fn` nested
+    /// two `mod`s deep, or `""` at crate root. Only used as a sort key in
+    /// [`KrateData::finalize`].
+    module: String,
+}
+
+#[derive(Debug)]
+struct StructDef {
+    name: String,
+    fields: Vec<syn::Ty>,
 }
 
 // C FFI for KrateData objects:
@@ -289,3 +564,79 @@ pub extern "C" fn krate_data_iter(krate: &KrateData, idx: size_t) -> *mut PyStri
         None => PyString::from("NO_IDX_ERROR").as_ptr(),
     }
 }
+
+/// Writes every collected function signature as a PEP 484 stub (`def name(...) -> ...: ...`)
+/// to a single `_rustypy.pyi` file under `out_dir`, giving the generated bindings editor
+/// autocompletion/type-checking the opaque `collected` wire strings can't provide on their own.
+/// `KrateData` has no notion of which source file a function came from by the time
+/// `collect_values` has run (`parse_src` walks every `.rs` file under a crate into the same flat
+/// `functions`/`collected` lists), so this emits one aggregate file rather than one per module.
+/// Returns a null pointer on success, or an error string the caller owns and must free.
+#[doc(hidden)]
+#[no_mangle]
+pub extern "C" fn krate_data_emit_stubs(krate: &KrateData, out_dir: *mut PyString) -> *mut PyString {
+    let out_dir = unsafe { PyString::from_ptr_to_string(out_dir) };
+    let out_path = Path::new(&out_dir).join("_rustypy.pyi");
+    let mut contents = String::new();
+    for line in &krate.stubs {
+        contents.push_str(line);
+        contents.push('\n');
+    }
+    match File::create(&out_path).and_then(|mut f| f.write_all(contents.as_bytes())) {
+        Ok(()) => ptr::null_mut::<PyString>(),
+        Err(err) => {
+            PyString::from(format!("failed to write stub file {}: {}",
+                                    out_path.to_string_lossy(),
+                                    err))
+                    .as_ptr()
+        }
+    }
+}
+
+#[cfg(test)]
+mod krate_data_tests {
+    use super::*;
+
+    fn parsed(src: &str) -> KrateData {
+        let mut krate = KrateData::new(vec!["".to_string()]);
+        parse_str(&mut krate, src).unwrap();
+        krate.finalize();
+        krate
+    }
+
+    #[test]
+    fn wire_repr_marks_local_structs_opaque_and_keeps_foreign_types_by_value() {
+        let krate = parsed(r#"
+            pub struct Foo { pub x: i32 }
+            pub fn takes_both(a: Foo, b: Vec<u8>) -> i32 { 0 }
+        "#);
+        assert_eq!(krate.collected[0],
+                   "takes_both::opaque(Foo);type(list type(u8));type(i32)");
+    }
+
+    #[test]
+    fn pyi_stub_spells_out_a_local_struct_and_maps_builtins_to_python_types() {
+        let krate = parsed(r#"
+            pub struct Foo { pub x: i32 }
+            pub fn takes_both(a: Foo, b: Vec<u8>) -> i32 { 0 }
+        "#);
+        assert_eq!(krate.stubs[0],
+                   "def takes_both(arg0: Foo, arg1: list[int]) -> int: ...");
+    }
+
+    #[test]
+    fn finalize_sorts_collected_functions_by_module_then_name() {
+        let krate = parsed(r#"
+            pub fn zeta() -> i32 { 0 }
+            pub fn alpha() -> i32 { 0 }
+            pub mod m {
+                pub fn beta() -> i32 { 0 }
+            }
+        "#);
+        let names: Vec<&str> = krate.stubs
+            .iter()
+            .map(|s| s.trim_start_matches("def ").split('(').next().unwrap())
+            .collect();
+        assert_eq!(names, vec!["alpha", "zeta", "beta"]);
+    }
+}
@@ -5,6 +5,7 @@ use libc::{size_t, c_char};
 use std::hash::Hash;
 use std::collections::HashMap;
 use std::convert::AsRef;
+use std::rc::Rc;
 
 #[doc(hidden)]
 #[macro_export]
@@ -35,15 +36,18 @@ macro_rules! _rustypy_abort_xtract_fail {
 
 pub mod pystring;
 pub mod pybool;
+pub mod pybuffer;
+pub mod pycell;
 pub mod pytuple;
 pub mod pylist;
+pub mod pyset;
 pub mod pydict;
 
 use self::pybool::PyBool;
 use self::pystring::PyString;
 use self::pytuple::PyTuple;
 use self::pylist::PyList;
-use self::pydict::{PyDict, PyDictKey};
+use self::pydict::{PyDict, PyDictKey, PyDictError};
 
 /// Enum type used to construct PyTuple and PyList types. All the kinds supported in Python
 /// are included here.
@@ -58,6 +62,12 @@ use self::pydict::{PyDict, PyDictKey};
 /// ```
 ///
 /// Likewise, all 'int' types are converted to signed 64-bit integers by default.
+///
+/// `PyTuple`/`PyList` are held behind an `Rc` rather than a `Box`, so cloning a `PyArg` that
+/// wraps one of them (eg. `pydict_get_element`'s read path) is a refcount bump instead of a deep
+/// structural copy. Handing such a value back across the FFI boundary for in-place mutation
+/// still needs a uniquely-owned value, so call sites that do so fall back to cloning the pointee
+/// via `Rc::try_unwrap`/`Rc::make_mut` only when another owner is still holding a reference.
 #[derive(Clone, Debug, PartialEq)]
 pub enum PyArg {
     I64(i64),
@@ -72,8 +82,8 @@ pub enum PyArg {
     F64(f64),
     PyBool(PyBool),
     PyString(PyString),
-    PyTuple(Box<PyTuple>),
-    PyList(Box<PyList>),
+    PyTuple(Rc<PyTuple>),
+    PyList(Rc<PyList>),
     PyDict(*mut size_t),
     None,
 }
@@ -82,6 +92,29 @@ impl PyArg {
     pub fn as_ptr(self) -> *mut PyArg {
         Box::into_raw(Box::new(self))
     }
+
+    /// Name of the variant currently held, for error messages that need to say what was found
+    /// instead of what was expected (ie. [PyTupleExtractError](pytuple/struct.PyTupleExtractError.html)).
+    pub fn variant_name(&self) -> &'static str {
+        match *self {
+            PyArg::I64(_) => "I64",
+            PyArg::I32(_) => "I32",
+            PyArg::I16(_) => "I16",
+            PyArg::I8(_) => "I8",
+            PyArg::U64(_) => "U64",
+            PyArg::U32(_) => "U32",
+            PyArg::U16(_) => "U16",
+            PyArg::U8(_) => "U8",
+            PyArg::F32(_) => "F32",
+            PyArg::F64(_) => "F64",
+            PyArg::PyBool(_) => "PyBool",
+            PyArg::PyString(_) => "PyString",
+            PyArg::PyTuple(_) => "PyTuple",
+            PyArg::PyList(_) => "PyList",
+            PyArg::PyDict(_) => "PyDict",
+            PyArg::None => "None",
+        }
+    }
 }
 
 macro_rules! pyarg_conversions {
@@ -115,6 +148,19 @@ macro_rules! pyarg_conversions {
                 }
             }
         }
+
+        impl ::std::convert::TryFrom<PyArg> for $type {
+            type Error = PyDictError;
+            fn try_from(a: PyArg) -> Result<$type, PyDictError> {
+                match a {
+                    $variant(v) => Ok(v),
+                    other => Err(PyDictError::UnexpectedValueType {
+                        expected: $repr,
+                        found: other.variant_name(),
+                    }),
+                }
+            }
+        }
     };
     (BOXED $type:ty; $variant:path; $repr:expr) => {
         impl AsRef<$type> for PyArg {
@@ -131,14 +177,14 @@ macro_rules! pyarg_conversions {
 
         impl From<$type> for PyArg {
             fn from(a: $type) -> PyArg {
-                $variant(Box::new(a))
+                $variant(Rc::new(a))
             }
         }
 
         impl From<PyArg> for $type {
             fn from(a: PyArg) -> $type {
                 match a {
-                    $variant(v) => *v,
+                    $variant(v) => Rc::try_unwrap(v).unwrap_or_else(|v| (*v).clone()),
                     _ => {
                         let msg = format!("expected a {} while destructuring PyArg enum", $repr);
                         _rustypy_abort_xtract_fail!(var msg);
@@ -146,6 +192,19 @@ macro_rules! pyarg_conversions {
                 }
             }
         }
+
+        impl ::std::convert::TryFrom<PyArg> for $type {
+            type Error = PyDictError;
+            fn try_from(a: PyArg) -> Result<$type, PyDictError> {
+                match a {
+                    $variant(v) => Ok(Rc::try_unwrap(v).unwrap_or_else(|v| (*v).clone())),
+                    other => Err(PyDictError::UnexpectedValueType {
+                        expected: $repr,
+                        found: other.variant_name(),
+                    }),
+                }
+            }
+        }
     }
 }
 
@@ -205,7 +264,7 @@ impl<T> From<Vec<T>> for PyArg
     where PyArg: From<T>
 {
     fn from(a: Vec<T>) -> PyArg {
-        PyArg::PyList(Box::new(PyList::from(a)))
+        PyArg::PyList(Rc::new(PyList::from(a)))
     }
 }
 
@@ -293,14 +352,14 @@ pub extern "C" fn pyarg_from_str(e: *const c_char) -> *mut PyArg {
 #[no_mangle]
 pub extern "C" fn pyarg_from_pytuple(e: *mut PyTuple) -> *mut PyArg {
     let e = unsafe { PyTuple::from_ptr(e) };
-    Box::into_raw(Box::new(PyArg::PyTuple(Box::new(e))))
+    Box::into_raw(Box::new(PyArg::PyTuple(Rc::new(e))))
 }
 
 #[doc(hidden)]
 #[no_mangle]
 pub extern "C" fn pyarg_from_pylist(e: *mut PyList) -> *mut PyArg {
     let e = unsafe { PyList::from_ptr(e) };
-    Box::into_raw(Box::new(PyArg::PyList(Box::new(e))))
+    Box::into_raw(Box::new(PyArg::PyList(Rc::new(e))))
 }
 
 #[doc(hidden)]
@@ -384,7 +443,7 @@ pub extern "C" fn pyarg_extract_owned_str(e: *mut PyArg) -> *mut PyString {
 pub extern "C" fn pyarg_extract_owned_tuple(e: *mut PyArg) -> *mut PyTuple {
     let e = unsafe { *(Box::from_raw(e)) };
     match e {
-        PyArg::PyTuple(val) => (*val).as_ptr(),
+        PyArg::PyTuple(val) => Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone()).as_ptr(),
         _ => _rustypy_abort_xtract_fail!("failed while trying to extract a PyTuple"),
     }
 }
@@ -394,11 +453,27 @@ pub extern "C" fn pyarg_extract_owned_tuple(e: *mut PyArg) -> *mut PyTuple {
 pub extern "C" fn pyarg_extract_owned_list(e: *mut PyArg) -> *mut PyList {
     let e = unsafe { *(Box::from_raw(e)) };
     match e {
-        PyArg::PyList(val) => (*val).as_ptr(),
+        PyArg::PyList(val) => Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone()).as_ptr(),
         _ => _rustypy_abort_xtract_fail!("failed while trying to extract a PyList"),
     }
 }
 
+/// Forces a deep, independent copy of `*ptr` — the explicit counterpart to the cheap `Rc`
+/// refcount bump an ordinary `PyArg::clone()` now performs on `PyTuple`/`PyList` values. Useful
+/// after [pydict_get_element_shared](pydict/fn.pydict_get_element_shared.html), when the caller
+/// actually needs an owned value it can mutate without affecting other holders of the same `Rc`.
+/// Does not take ownership of, or free, `ptr`.
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn pyarg_clone_into_owned(ptr: *mut PyArg) -> *mut PyArg {
+    let deep = match &*ptr {
+        PyArg::PyTuple(v) => PyArg::PyTuple(Rc::new((**v).clone())),
+        PyArg::PyList(v) => PyArg::PyList(Rc::new((**v).clone())),
+        other => other.clone(),
+    };
+    Box::into_raw(Box::new(deep))
+}
+
 #[doc(hidden)]
 #[no_mangle]
 pub extern "C" fn pyarg_extract_owned_dict(e: *mut PyArg) -> *mut size_t {
@@ -0,0 +1,184 @@
+//! An analog of a Python boolean type.
+//!
+//! It supports `&` and `|` operators, and comparison to Rust `bool` types.
+//! To return to Python use the `as_ptr` method and return a raw pointer.
+//!
+//! # Safety
+//! You can convert a raw pointer to a bool type with `from_ptr_into_bool` method,
+//! or to a `&PyBool` with `from_ptr` method. Those operations are unsafe as they require
+//! dereferencing a raw pointer.
+
+use std::ops::{BitAnd, BitOr, Not};
+
+/// An analog of a Python boolean type.
+///
+/// Read the [module docs](index.html) for more information.
+#[derive(Debug)]
+#[repr(C)]
+pub struct PyBool {
+    pub val: u8,
+}
+
+impl PyBool {
+    pub unsafe fn from_ptr(ptr: *mut PyBool) -> &'static PyBool {
+        &*ptr
+    }
+    pub unsafe fn from_ptr_into_bool(ptr: *mut PyBool) -> bool {
+        let ptr: &PyBool = &*ptr;
+        match ptr.val {
+            0 => false,
+            _ => true,
+        }
+    }
+    pub fn to_bool(&self) -> bool {
+        match self.val {
+            0 => false,
+            _ => true,
+        }
+    }
+    pub fn as_ptr(self) -> *mut PyBool {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+impl From<bool> for PyBool {
+    fn from(b: bool) -> PyBool {
+        let val = match b {
+            true => 1,
+            false => 0,
+        };
+        PyBool { val: val }
+    }
+}
+
+impl<'a> From<&'a bool> for PyBool {
+    fn from(b: &'a bool) -> PyBool {
+        let val = match b {
+            &true => 1,
+            &false => 0,
+        };
+        PyBool { val: val }
+    }
+}
+
+impl PartialEq<bool> for PyBool {
+    fn eq(&self, other: &bool) -> bool {
+        if self.val == 0 && *other == false {
+            true
+        } else if self.val == 1 && *other == true {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<'a> PartialEq<bool> for &'a PyBool {
+    fn eq(&self, other: &bool) -> bool {
+        if self.val == 0 && *other == false {
+            true
+        } else if self.val == 1 && *other == true {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Not for PyBool {
+    type Output = bool;
+    fn not(self) -> bool {
+        match self.val {
+            0 => false,
+            _ => true,
+        }
+    }
+}
+
+impl BitAnd<bool> for PyBool {
+    type Output = bool;
+    fn bitand(self, rhs: bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val & rhs
+    }
+}
+
+impl<'a> BitAnd<bool> for &'a PyBool {
+    type Output = bool;
+    fn bitand(self, rhs: bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val & rhs
+    }
+}
+
+impl<'a> BitAnd<&'a bool> for PyBool {
+    type Output = bool;
+    fn bitand(self, rhs: &'a bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val & rhs
+    }
+}
+
+impl<'a, 'b> BitAnd<&'a bool> for &'b PyBool {
+    type Output = bool;
+    fn bitand(self, rhs: &'a bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val & rhs
+    }
+}
+
+impl BitOr<bool> for PyBool {
+    type Output = bool;
+    fn bitor(self, rhs: bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val | rhs
+    }
+}
+
+impl<'a> BitOr<bool> for &'a PyBool {
+    type Output = bool;
+    fn bitor(self, rhs: bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val | rhs
+    }
+}
+
+impl<'a> BitOr<&'a bool> for PyBool {
+    type Output = bool;
+    fn bitor(self, rhs: &'a bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val | rhs
+    }
+}
+
+impl<'a, 'b> BitOr<&'a bool> for &'b PyBool {
+    type Output = bool;
+    fn bitor(self, rhs: &'a bool) -> bool {
+        let val = match self.val {
+            0 => false,
+            _ => true,
+        };
+        val | rhs
+    }
+}
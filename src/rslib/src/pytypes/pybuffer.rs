@@ -0,0 +1,259 @@
+//! A zero-copy, bulk-transfer buffer for a contiguous run of homogeneous numeric data.
+//!
+//! Moving a large numeric collection through [PyList](../pylist/index.html) means boxing every
+//! element into its own `PyArg` on the way in and back out, which is an allocation per element
+//! in both directions. `PyBuffer<T>` instead wraps a single `Vec<T>` allocation and exposes it
+//! across the FFI boundary as one pointer, one length and one `struct`-module format character
+//! (`typecode`, e.g. `'q'` for `i64` or `'d'` for `f64`), the same vocabulary Python's own
+//! buffer protocol (PEP 3118) uses for `memoryview`/`numpy.frombuffer`. The Python side wraps
+//! `pybuffer_*_data`/`pybuffer_*_len`/`pybuffer_*_typecode` with `memoryview` or
+//! `numpy.frombuffer` instead of walking the buffer element by element.
+//!
+//! Since the typecode has to be known at the point the FFI boundary is crossed, there is one
+//! concrete monomorphization per supported scalar type rather than a single generic entry
+//! point, following the same convention as
+//! [scalar_fastpath!](../pylist/macro.scalar_fastpath!.html) in `pylist`.
+
+use libc::{c_char, c_void, size_t};
+
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+
+/// Associates a scalar type with the `struct`-module format character used to describe it in
+/// Python's buffer protocol, so [`PyBuffer::from_vec`] can derive `typecode` from `T` instead
+/// of taking it (and risking a mismatch) as a separate argument.
+trait BufferTypecode {
+    const TYPECODE: c_char;
+}
+
+macro_rules! buffer_typecode {
+    ($ty:ty, $code:expr) => {
+        impl BufferTypecode for $ty {
+            const TYPECODE: c_char = $code as c_char;
+        }
+    };
+}
+
+buffer_typecode!(i8, 'b');
+buffer_typecode!(i16, 'h');
+buffer_typecode!(i32, 'i');
+buffer_typecode!(i64, 'q');
+buffer_typecode!(u8, 'B');
+buffer_typecode!(f32, 'f');
+buffer_typecode!(f64, 'd');
+
+/// A contiguous, single-allocation buffer of `T`, crossing the FFI boundary as a raw pointer,
+/// an element count and an `itemsize`/`typecode` pair describing `T`'s layout.
+///
+/// # Safety
+/// Like the other pytypes, `PyBuffer` must be passed between Rust and Python as a raw pointer,
+/// obtained with [`as_ptr`](PyBuffer::as_ptr) and consumed with the unsafe
+/// [`from_ptr`](PyBuffer::from_ptr). Dropping a `PyBuffer<T>` reconstructs and drops the exact
+/// `Vec<T>` it was built from, so it must never be freed as anything other than the `T` it was
+/// constructed with.
+pub struct PyBuffer<T> {
+    ptr: *mut c_void,
+    len: size_t,
+    capacity: size_t,
+    itemsize: size_t,
+    typecode: c_char,
+    _marker: PhantomData<T>,
+}
+
+impl<T: BufferTypecode> PyBuffer<T> {
+    /// Builds a `PyBuffer` from an owned `Vec<T>`, with no per-element copying: the vector's
+    /// own allocation becomes the buffer's backing storage. `itemsize`/`typecode` are derived
+    /// from `T` via [`BufferTypecode`], so they can never drift out of sync with the data.
+    pub fn from_vec(mut v: Vec<T>) -> PyBuffer<T> {
+        v.shrink_to_fit();
+        let len = v.len();
+        // `shrink_to_fit` doesn't guarantee `capacity() == len()`, just that it's no
+        // bigger than it has to be, so the actual capacity has to be captured here and
+        // carried alongside `len` for `Drop` to reconstruct the `Vec` correctly.
+        let capacity = v.capacity();
+        let ptr = v.as_mut_ptr() as *mut c_void;
+        mem::forget(v);
+        PyBuffer {
+            ptr: ptr,
+            len: len as size_t,
+            capacity: capacity as size_t,
+            itemsize: mem::size_of::<T>() as size_t,
+            typecode: T::TYPECODE,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PyBuffer<T> {
+    /// Reinterprets the buffer's contents as a `&[T]`, with no copy.
+    pub unsafe fn as_slice(&self) -> &[T] {
+        slice::from_raw_parts(self.ptr as *const T, self.len as usize)
+    }
+
+    pub fn data(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    pub fn len(&self) -> size_t {
+        self.len
+    }
+
+    pub fn itemsize(&self) -> size_t {
+        self.itemsize
+    }
+
+    pub fn typecode(&self) -> c_char {
+        self.typecode
+    }
+
+    /// Get a PyBuffer from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyBuffer<T>) -> PyBuffer<T> {
+        *(Box::from_raw(ptr))
+    }
+
+    /// Return a PyBuffer as a raw pointer.
+    pub fn as_ptr(self) -> *mut PyBuffer<T> {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+impl<T> Drop for PyBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            Vec::from_raw_parts(self.ptr as *mut T, self.len as usize, self.capacity as usize);
+        }
+    }
+}
+
+/// Generates the concrete, `#[no_mangle]` FFI surface for one scalar `PyBuffer<$ty>`
+/// monomorphization: a constructor that copies an incoming `ptr`/`len` buffer (e.g. from a
+/// `numpy`/`array.array` buffer handed over by Python) into a `PyBuffer`, plus the
+/// `data`/`len`/`typecode`/`free` accessors a caller needs to wrap the result in a `memoryview`.
+macro_rules! pybuffer_scalar {
+    ($ty:ty, $new_fn:ident, $data_fn:ident, $len_fn:ident, $typecode_fn:ident, $free_fn:ident) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn $new_fn(ptr: *const $ty, len: size_t) -> *mut PyBuffer<$ty> {
+            let src = slice::from_raw_parts(ptr, len as usize);
+            PyBuffer::from_vec(src.to_vec()).as_ptr()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $data_fn(ptr: *mut PyBuffer<$ty>) -> *mut c_void {
+            (&*ptr).data()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $len_fn(ptr: *mut PyBuffer<$ty>) -> size_t {
+            (&*ptr).len()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $typecode_fn(ptr: *mut PyBuffer<$ty>) -> c_char {
+            (&*ptr).typecode()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $free_fn(ptr: *mut PyBuffer<$ty>) {
+            if ptr.is_null() {
+                return;
+            }
+            PyBuffer::from_ptr(ptr);
+        }
+    };
+}
+
+pybuffer_scalar!(
+    i8,
+    pybuffer_i8_new,
+    pybuffer_i8_data,
+    pybuffer_i8_len,
+    pybuffer_i8_typecode,
+    pybuffer_i8_free
+);
+pybuffer_scalar!(
+    i16,
+    pybuffer_i16_new,
+    pybuffer_i16_data,
+    pybuffer_i16_len,
+    pybuffer_i16_typecode,
+    pybuffer_i16_free
+);
+pybuffer_scalar!(
+    i32,
+    pybuffer_i32_new,
+    pybuffer_i32_data,
+    pybuffer_i32_len,
+    pybuffer_i32_typecode,
+    pybuffer_i32_free
+);
+pybuffer_scalar!(
+    i64,
+    pybuffer_i64_new,
+    pybuffer_i64_data,
+    pybuffer_i64_len,
+    pybuffer_i64_typecode,
+    pybuffer_i64_free
+);
+pybuffer_scalar!(
+    u8,
+    pybuffer_u8_new,
+    pybuffer_u8_data,
+    pybuffer_u8_len,
+    pybuffer_u8_typecode,
+    pybuffer_u8_free
+);
+pybuffer_scalar!(
+    f32,
+    pybuffer_f32_new,
+    pybuffer_f32_data,
+    pybuffer_f32_len,
+    pybuffer_f32_typecode,
+    pybuffer_f32_free
+);
+pybuffer_scalar!(
+    f64,
+    pybuffer_f64_new,
+    pybuffer_f64_data,
+    pybuffer_f64_len,
+    pybuffer_f64_typecode,
+    pybuffer_f64_free
+);
+
+#[cfg(test)]
+mod pybuffer_tests {
+    use super::*;
+
+    #[test]
+    fn from_vec_round_trips_through_as_slice() {
+        let buf = PyBuffer::from_vec(vec![1i64, 2, 3]);
+        unsafe { assert_eq!(buf.as_slice(), &[1, 2, 3]) };
+        assert_eq!(buf.itemsize(), mem::size_of::<i64>() as size_t);
+        assert_eq!(buf.typecode(), 'q' as c_char);
+    }
+
+    #[test]
+    fn drop_uses_the_tracked_capacity_not_len() {
+        // Built by hand with capacity deliberately left larger than len, the case
+        // `shrink_to_fit` doesn't rule out: dropping with the wrong capacity here would
+        // hand the allocator a `Vec::from_raw_parts` size/layout it never allocated, UB
+        // on any allocator that doesn't happen to round up to exactly `len`.
+        let mut v: Vec<i32> = Vec::with_capacity(8);
+        v.push(42);
+        let len = v.len();
+        let capacity = v.capacity();
+        assert!(capacity > len, "test setup needs slack between len and capacity");
+        let ptr = v.as_mut_ptr() as *mut c_void;
+        mem::forget(v);
+        let buf = PyBuffer::<i32> {
+            ptr: ptr,
+            len: len as size_t,
+            capacity: capacity as size_t,
+            itemsize: mem::size_of::<i32>() as size_t,
+            typecode: i32::TYPECODE,
+            _marker: PhantomData,
+        };
+        unsafe { assert_eq!(buf.as_slice(), &[42]) };
+        drop(buf);
+    }
+}
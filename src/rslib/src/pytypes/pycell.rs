@@ -0,0 +1,378 @@
+//! Runtime borrow tracking for raw-pointer handles, in the spirit of PyO3's `PyCell`.
+//!
+//! `from_ptr`/`as_ptr` (and the list/dict macros built on top of them) hand out raw `*mut`
+//! pointers with no aliasing protection at all: nothing stops a Python caller that kept two
+//! references to the same `PyList` from mutating through both at once, which is instant UB.
+//! `PyCell<T>` wraps a payload with a `Cell<isize>` borrow flag (`0` = unborrowed, `> 0` = N
+//! shared borrows, `-1` = mutably borrowed) and enforces the usual `RefCell` discipline:
+//! any number of concurrent shared borrows, or exactly one exclusive borrow, never both.
+//!
+//! Rust code on either side of the FFI boundary goes through [`try_borrow`](PyCell::try_borrow)/
+//! [`try_borrow_mut`](PyCell::try_borrow_mut), which hand back a guard
+//! ([`PyRef`]/[`PyRefMut`]) that releases the borrow when dropped. Since a Rust guard can't
+//! survive a round trip through a C caller, the FFI entry points (`pylist_cell_try_borrow` and
+//! friends, generated per handle type by [`py_cell_ffi!`]) instead return the borrowed pointer
+//! through an out-parameter and forget the guard, pairing it with a `pylist_cell_release_*`
+//! call the Python wrapper must make once it's done — mirroring how a Python `memoryview`
+//! explicitly `release()`s instead of relying on a destructor running at a known time.
+//! The `*_cell_new` constructors always start a fresh `PyCell` with the flag at zero; `*_free` asserts it's back
+//! at zero, since freeing a still-borrowed cell would leave a dangling reference behind.
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Returned by [`PyCell::try_borrow`]/[`try_borrow_mut`](PyCell::try_borrow_mut) (and the FFI
+/// entry points built on them) instead of letting a second, aliasing borrow through.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PyBorrowError {
+    /// A shared borrow was requested while the cell was already borrowed mutably.
+    AlreadyMutablyBorrowed,
+    /// A mutable borrow was requested while the cell already had at least one shared or
+    /// mutable borrow outstanding.
+    AlreadyBorrowed,
+}
+
+impl fmt::Display for PyBorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PyBorrowError::AlreadyMutablyBorrowed => {
+                write!(f, "already mutably borrowed, can't take a shared borrow")
+            }
+            PyBorrowError::AlreadyBorrowed => {
+                write!(f, "already borrowed, can't take a mutable borrow")
+            }
+        }
+    }
+}
+
+impl Error for PyBorrowError {}
+
+/// A payload plus a `Cell<isize>` borrow flag tracking outstanding shared/exclusive borrows of
+/// it. Read the [module docs](index.html) for more information.
+pub struct PyCell<T> {
+    borrow: Cell<isize>,
+    inner: T,
+}
+
+impl<T> PyCell<T> {
+    /// Wraps `inner` in a freshly allocated cell, with the borrow flag at zero.
+    pub fn new(inner: T) -> PyCell<T> {
+        PyCell {
+            borrow: Cell::new(0),
+            inner: inner,
+        }
+    }
+
+    /// Attempts a shared borrow. Fails if the cell is already borrowed mutably.
+    pub fn try_borrow(&self) -> Result<PyRef<T>, PyBorrowError> {
+        let b = self.borrow.get();
+        if b < 0 {
+            return Err(PyBorrowError::AlreadyMutablyBorrowed);
+        }
+        self.borrow.set(b + 1);
+        Ok(PyRef { cell: self })
+    }
+
+    /// Attempts an exclusive borrow. Fails if the cell already has any outstanding borrow,
+    /// shared or exclusive.
+    pub fn try_borrow_mut(&self) -> Result<PyRefMut<T>, PyBorrowError> {
+        if self.borrow.get() != 0 {
+            return Err(PyBorrowError::AlreadyBorrowed);
+        }
+        self.borrow.set(-1);
+        Ok(PyRefMut { cell: self })
+    }
+
+    /// The current borrow flag: `0` unborrowed, `N > 0` shared borrows, `-1` mutably borrowed.
+    pub fn borrow_flag(&self) -> isize {
+        self.borrow.get()
+    }
+
+    /// Get a PyCell from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyCell<T>) -> PyCell<T> {
+        *(Box::from_raw(ptr))
+    }
+
+    /// Return a PyCell as a raw pointer.
+    pub fn as_ptr(self) -> *mut PyCell<T> {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Raw, FFI-facing borrow increment: bumps the flag exactly like
+    /// [`try_borrow`](PyCell::try_borrow) but returns a plain reference with no guard to release
+    /// it, since a Rust guard can't cross the FFI boundary. Pair with
+    /// [`release_borrow`](PyCell::release_borrow).
+    pub fn raw_try_borrow(&self) -> Result<&T, PyBorrowError> {
+        let b = self.borrow.get();
+        if b < 0 {
+            return Err(PyBorrowError::AlreadyMutablyBorrowed);
+        }
+        self.borrow.set(b + 1);
+        Ok(&self.inner)
+    }
+
+    /// Raw, FFI-facing mutable borrow increment, paired with
+    /// [`release_borrow_mut`](PyCell::release_borrow_mut). See
+    /// [`raw_try_borrow`](PyCell::raw_try_borrow).
+    pub fn raw_try_borrow_mut(&self) -> Result<*mut T, PyBorrowError> {
+        if self.borrow.get() != 0 {
+            return Err(PyBorrowError::AlreadyBorrowed);
+        }
+        self.borrow.set(-1);
+        Ok(&self.inner as *const T as *mut T)
+    }
+
+    /// Releases one shared borrow previously taken with
+    /// [`raw_try_borrow`](PyCell::raw_try_borrow).
+    pub fn release_borrow(&self) {
+        let b = self.borrow.get();
+        debug_assert!(b > 0, "releasing a shared borrow that was never taken");
+        self.borrow.set(b - 1);
+    }
+
+    /// Releases the exclusive borrow previously taken with
+    /// [`raw_try_borrow_mut`](PyCell::raw_try_borrow_mut).
+    pub fn release_borrow_mut(&self) {
+        debug_assert_eq!(
+            self.borrow.get(),
+            -1,
+            "releasing a mutable borrow that was never taken"
+        );
+        self.borrow.set(0);
+    }
+}
+
+/// A shared, guarded borrow of a [`PyCell`]'s payload. Releases the borrow when dropped.
+pub struct PyRef<'a, T: 'a> {
+    cell: &'a PyCell<T>,
+}
+
+impl<'a, T> Deref for PyRef<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.cell.inner
+    }
+}
+
+impl<'a, T> Drop for PyRef<'a, T> {
+    fn drop(&mut self) {
+        self.cell.release_borrow();
+    }
+}
+
+/// An exclusive, guarded borrow of a [`PyCell`]'s payload. Releases the borrow when dropped.
+pub struct PyRefMut<'a, T: 'a> {
+    cell: &'a PyCell<T>,
+}
+
+impl<'a, T> Deref for PyRefMut<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.cell.inner
+    }
+}
+
+impl<'a, T> DerefMut for PyRefMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *(&self.cell.inner as *const T as *mut T) }
+    }
+}
+
+impl<'a, T> Drop for PyRefMut<'a, T> {
+    fn drop(&mut self) {
+        self.cell.release_borrow_mut();
+    }
+}
+
+/// Generates the concrete, `#[no_mangle]` FFI surface for a `PyCell<$ty>` borrow-tracked
+/// handle: matched `try_borrow`/`release_borrow` and `try_borrow_mut`/`release_borrow_mut`
+/// pairs, and a destructor that aborts rather than freeing a still-borrowed cell. The
+/// constructor is written by hand at each call site instead, since wrapping the incoming raw
+/// pointer into an owned `$ty` isn't quite the same move for every handle (`PyDict`'s `*mut
+/// usize` needs an extra [PyDictHandle](../pydict/struct.PyDictHandle.html) wrap that
+/// `PyList`/`PyTuple` don't).
+macro_rules! py_cell_ffi {
+    ($ty:ty,
+     $try_borrow_fn:ident,
+     $release_fn:ident,
+     $try_borrow_mut_fn:ident,
+     $release_mut_fn:ident,
+     $free_fn:ident) => {
+        /// Returns an error string (the caller owns and must free it) and leaves `out`
+        /// untouched instead of aliasing if the cell is already mutably borrowed;
+        /// otherwise writes the borrowed pointer to `out` and returns a null pointer.
+        #[no_mangle]
+        pub unsafe extern "C" fn $try_borrow_fn(
+            cell: &PyCell<$ty>,
+            out: *mut *const $ty,
+        ) -> *mut PyString {
+            match cell.raw_try_borrow() {
+                Ok(inner) => {
+                    *out = inner as *const $ty;
+                    ::std::ptr::null_mut()
+                }
+                Err(err) => PyString::from(err.to_string()).as_ptr(),
+            }
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $release_fn(cell: &PyCell<$ty>) {
+            cell.release_borrow();
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $try_borrow_mut_fn(
+            cell: &PyCell<$ty>,
+            out: *mut *mut $ty,
+        ) -> *mut PyString {
+            match cell.raw_try_borrow_mut() {
+                Ok(inner) => {
+                    *out = inner;
+                    ::std::ptr::null_mut()
+                }
+                Err(err) => PyString::from(err.to_string()).as_ptr(),
+            }
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $release_mut_fn(cell: &PyCell<$ty>) {
+            cell.release_borrow_mut();
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $free_fn(ptr: *mut PyCell<$ty>) {
+            if ptr.is_null() {
+                return;
+            }
+            let cell = PyCell::from_ptr(ptr);
+            if cell.borrow_flag() != 0 {
+                _rustypy_abort_xtract_fail!(
+                    "tried to free a PyCell with an outstanding borrow still live");
+            }
+        }
+    };
+}
+
+use pytypes::pydict::PyDictHandle;
+use pytypes::pylist::PyList;
+use pytypes::pystring::PyString;
+use pytypes::pytuple::PyTuple;
+
+/// Takes ownership of an existing `*mut PyList` and wraps it in a fresh, unborrowed `PyCell`.
+#[no_mangle]
+pub unsafe extern "C" fn pylist_cell_new(ptr: *mut PyList) -> *mut PyCell<PyList> {
+    let inner = PyList::from_ptr(ptr);
+    PyCell::new(inner).as_ptr()
+}
+
+py_cell_ffi!(
+    PyList,
+    pylist_cell_try_borrow,
+    pylist_cell_release_borrow,
+    pylist_cell_try_borrow_mut,
+    pylist_cell_release_borrow_mut,
+    pylist_cell_free
+);
+
+/// Takes ownership of an existing `*mut PyTuple` and wraps it in a fresh, unborrowed `PyCell`.
+#[no_mangle]
+pub unsafe extern "C" fn pytuple_cell_new(ptr: *mut PyTuple) -> *mut PyCell<PyTuple> {
+    let inner = PyTuple::from_ptr(ptr);
+    PyCell::new(inner).as_ptr()
+}
+
+py_cell_ffi!(
+    PyTuple,
+    pytuple_cell_try_borrow,
+    pytuple_cell_release_borrow,
+    pytuple_cell_try_borrow_mut,
+    pytuple_cell_release_borrow_mut,
+    pytuple_cell_free
+);
+
+/// Wraps the type-erased `*mut usize` a `PyDict`'s own [`as_ptr`](../pydict/struct.PyDict.html)
+/// returns in a fresh, unborrowed `PyCell`.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_cell_new(ptr: *mut usize) -> *mut PyCell<PyDictHandle> {
+    PyCell::new(PyDictHandle(ptr)).as_ptr()
+}
+
+py_cell_ffi!(
+    PyDictHandle,
+    pydict_cell_try_borrow,
+    pydict_cell_release_borrow,
+    pydict_cell_try_borrow_mut,
+    pydict_cell_release_borrow_mut,
+    pydict_cell_free
+);
+
+#[cfg(test)]
+mod borrow_tracking_tests {
+    use super::*;
+
+    #[test]
+    fn shared_borrow_is_rejected_while_mutably_borrowed() {
+        let cell = PyCell::new(1i32);
+        let _guard = cell.try_borrow_mut().unwrap();
+        assert_eq!(
+            cell.try_borrow().unwrap_err(),
+            PyBorrowError::AlreadyMutablyBorrowed
+        );
+    }
+
+    #[test]
+    fn mutable_borrow_is_rejected_while_shared_borrowed() {
+        let cell = PyCell::new(1i32);
+        let _guard = cell.try_borrow().unwrap();
+        assert_eq!(
+            cell.try_borrow_mut().unwrap_err(),
+            PyBorrowError::AlreadyBorrowed
+        );
+    }
+
+    #[test]
+    fn mutable_borrow_is_rejected_while_already_mutably_borrowed() {
+        let cell = PyCell::new(1i32);
+        let _guard = cell.try_borrow_mut().unwrap();
+        assert_eq!(
+            cell.try_borrow_mut().unwrap_err(),
+            PyBorrowError::AlreadyBorrowed
+        );
+    }
+
+    #[test]
+    fn releasing_a_guarded_borrow_allows_a_later_borrow() {
+        let cell = PyCell::new(1i32);
+        {
+            let _guard = cell.try_borrow_mut().unwrap();
+        }
+        assert_eq!(cell.borrow_flag(), 0);
+        assert!(cell.try_borrow().is_ok());
+    }
+
+    #[test]
+    fn multiple_shared_guards_can_coexist() {
+        let cell = PyCell::new(1i32);
+        let first = cell.try_borrow().unwrap();
+        let second = cell.try_borrow().unwrap();
+        assert_eq!(cell.borrow_flag(), 2);
+        drop(first);
+        drop(second);
+        assert_eq!(cell.borrow_flag(), 0);
+    }
+
+    #[test]
+    fn raw_borrow_rejects_aliasing_and_release_reopens_it() {
+        let cell = PyCell::new(1i32);
+        assert!(cell.raw_try_borrow_mut().is_ok());
+        assert_eq!(
+            cell.raw_try_borrow().unwrap_err(),
+            PyBorrowError::AlreadyMutablyBorrowed
+        );
+        cell.release_borrow_mut();
+        assert_eq!(cell.borrow_flag(), 0);
+        assert!(cell.raw_try_borrow().is_ok());
+    }
+}
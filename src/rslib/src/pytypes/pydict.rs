@@ -34,22 +34,99 @@
 //! When extracting in Python with the FFI, elements are moved, not copied
 //! and when free'd all the original elements are dropped.
 //!
+//! ## Bulk (de)serialization over FFI
+//! Draining element-by-element via `pydict_get_drain`/`pydict_drain_element` costs one FFI
+//! round-trip per entry, and nested containers (`PyList`, `PyDict`, `PyTuple`) each add another
+//! round of pointer-chasing on top of that. [pydict_to_cbor](fn.pydict_to_cbor.html) and
+//! [pydict_from_cbor](fn.pydict_from_cbor.html) (de)serialize the whole value tree as a single
+//! CBOR buffer instead: scalars map to the obvious CBOR primitive, `PyList` becomes a CBOR
+//! array, `PyDict` a CBOR map, and `PyTuple` an array wrapped in `CBOR_TUPLE_TAG` so it
+//! round-trips distinctly from a plain list. A nested `PyDict` is assumed to share its parent's
+//! key type, since a type-erased `PyArg::PyDict` carries no key-type tag of its own.
+//!
 //! ## Unpacking PyDict from Python
 //! Is recommended to use the [unpack_pydict!](../../macro.unpack_pydict!.html) macro in order
 //! to convert a PyDict to a Rust native type. Check the macro documentation for more info.
+//!
+//! `unpack_pydict!`, `pydict_insert` and `From<PyArg> for PyDict` all abort the process via
+//! `_rustypy_abort_xtract_fail!` on a type mismatch, which is fatal if rustypy is embedded in a
+//! long-running host. [PyDictError](struct.PyDictError.html) plus the `try_from_ptr`/
+//! `try_into_hashmap`/[try_unpack_pydict!](../../macro.try_unpack_pydict!.html) siblings return
+//! a `Result` instead, and `pydict_insert_checked`/`pydict_get_element_checked` expose the same
+//! over FFI as a status code.
+//!
+//! `pydict_get_element` clones the looked-up `PyArg` out of the dict, which used to mean a full
+//! structural copy of any nested `PyTuple`/`PyList` on every read. Those two `PyArg` variants are
+//! now held behind an `Rc`, so the clone is a refcount bump instead; [pydict_get_element_shared]
+//! (fn.pydict_get_element_shared.html) exposes this directly, handing back a `*mut PyArg` that
+//! shares its storage with the value still in the dict, with `pyarg_clone_into_owned` available
+//! when an independent, mutable copy is actually needed.
 
 use libc::size_t;
 use super::PyArg;
 use super::pybool::PyBool;
 use super::pystring::PyString;
 use super::pytuple::PyTuple;
+use super::pylist::PyList;
+
+use serde_cbor::Value as CborValue;
 
 use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
 use std::collections::hash_map::Drain;
+use std::collections::hash_map::IntoIter as HashMapIntoIter;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 use std::marker::PhantomData;
 use std::hash::Hash;
 use std::iter::FromIterator;
+use std::mem;
 use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+/// Error returned by the fallible extraction API (`PyDict::try_from_ptr`, `try_into_hashmap`
+/// and [try_unpack_pydict!](../../macro.try_unpack_pydict!.html)), carrying enough detail for a
+/// Rust caller to recover instead of the process aborting like the unchecked API does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PyDictError {
+    /// A key's `PyArg` variant didn't match the dict's declared key type.
+    UnexpectedKeyType { expected: &'static str, found: &'static str },
+    /// A value's `PyArg` variant didn't match the type it was being converted into.
+    UnexpectedValueType { expected: &'static str, found: &'static str },
+    /// A raw pointer that should have pointed to a `PyDict` was null.
+    NullPointer,
+}
+
+impl fmt::Display for PyDictError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PyDictError::UnexpectedKeyType { expected, found } => write!(
+                f,
+                "PyDict key: expected {}, found {}",
+                expected, found
+            ),
+            PyDictError::UnexpectedValueType { expected, found } => write!(
+                f,
+                "PyDict value: expected {}, found {}",
+                expected, found
+            ),
+            PyDictError::NullPointer => write!(f, "expected a PyDict pointer, found a null pointer"),
+        }
+    }
+}
+
+impl Error for PyDictError {}
+
+/// An opaque carrier for the type-erased `*mut usize` pointer [PyDict::as_ptr](PyDict::as_ptr)
+/// returns. `PyDict<K>` itself can't be named generically at the FFI boundary (the pointer
+/// carries no tag of which `K` it was boxed with), so code that needs to hand the raw pointer
+/// to something type-erased too — like [PyCell](../pycell/struct.PyCell.html) — wraps it in
+/// this newtype instead of passing the bare pointer around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PyDictHandle(pub *mut usize);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PyDict<K, PyArg>
@@ -101,6 +178,21 @@ impl<K> PyDict<K,PyArg>
         self.table.drain()
     }
 
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key(&mut self, k: &K) -> bool {
+        self.table.contains_key(k)
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.table.clear()
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
     /// Get a PyDict from a previously boxed PyDict.
     ///
     /// Takes two type parameters:
@@ -123,6 +215,17 @@ impl<K> PyDict<K,PyArg>
         *(Box::from_raw(ptr as *mut PyDict<K, PyArg>))
     }
 
+    /// Like [from_ptr](PyDict::from_ptr), but returns `Err(PyDictError::NullPointer)` instead of
+    /// dereferencing a null pointer. The key type itself still can't be checked here: a
+    /// `*mut usize` carries no tag of which `K` it was boxed with, the same limitation the
+    /// module docs note for nested `PyArg::PyDict` values.
+    pub unsafe fn try_from_ptr(ptr: *mut usize) -> Result<PyDict<K, PyArg>, PyDictError> {
+        if ptr.is_null() {
+            return Err(PyDictError::NullPointer);
+        }
+        Ok(Self::from_ptr(ptr))
+    }
+
     /// Returns self as raw pointer. Use this method when returning a PyTuple to Python.
     pub fn as_ptr(self) -> *mut usize {
         Box::into_raw(Box::new(self)) as *mut usize
@@ -135,6 +238,31 @@ impl<K> PyDict<K,PyArg>
     {
         HashMap::from_iter(self.table.drain().map(|(k, v)| (k, <V>::from(v))))
     }
+
+    /// Like [into_hashmap](PyDict::into_hashmap), but returns
+    /// `Err(PyDictError::UnexpectedValueType)` for the first value that doesn't match `V`
+    /// instead of aborting.
+    pub fn try_into_hashmap<V>(mut self) -> Result<HashMap<K, V>, PyDictError>
+        where V: TryFrom<PyArg, Error = PyDictError>
+    {
+        let mut out = HashMap::new();
+        for (k, v) in self.table.drain() {
+            out.insert(k, V::try_from(v)?);
+        }
+        Ok(out)
+    }
+
+    /// Like [try_into_hashmap](PyDict::try_into_hashmap), but checks each value against a
+    /// runtime-built [PyShape](enum.PyShape.html) instead of a compile-time `V`, for when the
+    /// shape of the values is only known at runtime.
+    pub fn convert_with(mut self, shape: &PyShape) -> Result<HashMap<K, PyArg>, PyDictError> {
+        let mut out = HashMap::new();
+        for (k, v) in self.table.drain() {
+            out.insert(k, validate_pyarg(v, shape)?);
+        }
+        Ok(out)
+    }
+
     /// Consume self and turn it into an iterator.
     pub fn into_iter<T: From<PyArg>>(self) -> IntoIter<K, T> {
         IntoIter {
@@ -291,7 +419,7 @@ macro_rules! unpack_pydict {
         dict
     }};
     ( $pytuple:ident; PyTuple { $t:tt } ) => {{
-        let mut unboxed = *($pytuple);
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pytuple).unwrap_or_else(|v| (*v).clone());
         unpack_pytuple!(unboxed; $t)
     }};
     ( $pylist:ident; PyList{ $($u:tt)* } ) => {{
@@ -312,6 +440,85 @@ macro_rules! unpack_pydict {
     }};
 }
 
+/// Result-returning sibling of [unpack_pydict!](macro.unpack_pydict!.html): instead of aborting
+/// the process on a value-type mismatch, yields
+/// `Result<_, `[`PyDictError`](pydict/struct.PyDictError.html)`>` carrying the expected/found
+/// variant names, so a Rust caller can recover from a malformed dict coming from dynamic Python
+/// data.
+///
+/// Supports the same `PyDict{(key_ty, Variant => type)}` and nested-`PyDict` spells as
+/// `unpack_pydict!`. A `PyTuple`/`PyList` field nested inside still delegates to the abort-based
+/// `unpack_pytuple!`/`unpack_pylist!`, the same scope limitation
+/// [try_unpack_pytuple!](../rustypy/macro.try_unpack_pytuple!.html) documents for its own nested
+/// containers.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main(){
+/// use rustypy::PyDict;
+/// use std::collections::HashMap;
+///
+/// let mut hm = HashMap::new();
+/// for (k, v) in vec![(0_i32, "Hello"), (1_i32, " "), (2_i32, "World!")] {
+///     hm.insert(k, v);
+/// }
+/// let dict = PyDict::from(hm).as_ptr();
+/// let unpacked = try_unpack_pydict!(dict; PyDict{(i32, PyString => String)});
+/// assert!(unpacked.is_ok());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_unpack_pydict {
+    ( $pydict:ident; PyDict{($kt:ty, $o:tt { $($t:tt)* })} ) => {{
+        use rustypy::{PyArg, PyDict, PyDictError};
+        (|| -> Result<_, PyDictError> {
+            let mut unboxed = unsafe { *(Box::from_raw($pydict as *mut PyDict<$kt, PyArg>)) };
+            use std::collections::HashMap;
+            let mut dict = HashMap::new();
+            for (k, v) in unboxed.drain() {
+                match v {
+                    PyArg::$o(val) => {
+                        let inner = try_unpack_pydict!(val; $o { $($t)* })?;
+                        dict.insert(k, inner);
+                    }
+                    other => return Err(PyDictError::UnexpectedValueType {
+                        expected: stringify!($o),
+                        found: other.variant_name(),
+                    }),
+                }
+            }
+            Ok(dict)
+        })()
+    }};
+    ( $pytuple:ident; PyTuple { $t:tt } ) => {{
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pytuple).unwrap_or_else(|v| (*v).clone());
+        Ok(unpack_pytuple!(unboxed; $t))
+    }};
+    ( $pylist:ident; PyList{ $($u:tt)* } ) => {{
+        Ok(unpack_pylist!( $pylist; PyList{ $($u)* } ))
+    }};
+    ( $pydict:ident; PyDict{($kt:ty, $t:tt => $type_:ty)} ) => {{
+        use rustypy::{PyArg, PyDict, PyDictError};
+        (|| -> Result<_, PyDictError> {
+            let mut unboxed = unsafe { *(Box::from_raw($pydict as *mut PyDict<$kt, PyArg>)) };
+            use std::collections::HashMap;
+            let mut dict = HashMap::new();
+            for (k, v) in unboxed.drain() {
+                match v {
+                    PyArg::$t(val) => { dict.insert(k, <$type_>::from(val)); },
+                    other => return Err(PyDictError::UnexpectedValueType {
+                        expected: stringify!($t),
+                        found: other.variant_name(),
+                    }),
+                }
+            }
+            Ok(dict)
+        })()
+    }};
+}
+
 #[no_mangle]
 pub extern "C" fn pydict_new(k_type: &PyDictK) -> *mut size_t {
     match *(k_type) {
@@ -355,6 +562,14 @@ pub extern "C" fn pydict_new(k_type: &PyDictK) -> *mut size_t {
             let d: PyDict<PyBool, PyArg> = PyDict::new();
             d.as_ptr() as *mut size_t
         }
+        PyDictK::F64 => {
+            let d: PyDict<PyFloatKey, PyArg> = PyDict::new();
+            d.as_ptr() as *mut size_t
+        }
+        PyDictK::Tuple => {
+            let d: PyDict<PyTupleKey, PyArg> = PyDict::new();
+            d.as_ptr() as *mut size_t
+        }
     }
 }
 
@@ -433,7 +648,313 @@ pub unsafe extern "C" fn pydict_insert(dict: *mut size_t,
             let value = *(Box::from_raw(value));
             dict.insert(key, value);
         }
+        PyDictK::F64 => {
+            let mut dict = &mut *(dict as *mut PyDict<PyFloatKey, PyArg>);
+            let key = PyFloatKey::from(_match_pyarg_in!(key; F64));
+            let value = *(Box::from_raw(value));
+            dict.insert(key, value);
+        }
+        PyDictK::Tuple => {
+            let mut dict = &mut *(dict as *mut PyDict<PyTupleKey, PyArg>);
+            let key_rc = _match_pyarg_in!(key; PyTuple);
+            let key = PyTupleKey::from_pytuple(
+                Rc::try_unwrap(key_rc).unwrap_or_else(|rc| (*rc).clone()),
+            );
+            let value = *(Box::from_raw(value));
+            dict.insert(key, value);
+        }
+    };
+}
+
+/// Non-aborting sibling of [pydict_insert](fn.pydict_insert.html): instead of killing the
+/// process on a key-type mismatch, frees `key`/`value` and returns `-1` so the caller (ie. a
+/// Python wrapper) can raise a `TypeError` instead. Returns `-2` if `dict` is null, `0` on
+/// success.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_insert_checked(dict: *mut size_t,
+                                               k_type: &PyDictK,
+                                               key: *mut PyArg,
+                                               value: *mut PyArg)
+                                               -> i32 {
+    if dict.is_null() {
+        drop(Box::from_raw(key));
+        drop(Box::from_raw(value));
+        return -2;
+    }
+    macro_rules! _insert_checked {
+        ($kt:ty; $variant:ident) => {{
+            match *(Box::from_raw(key)) {
+                PyArg::$variant(k) => {
+                    let dict = &mut *(dict as *mut PyDict<$kt, PyArg>);
+                    let value = *(Box::from_raw(value));
+                    dict.insert(k, value);
+                    0
+                }
+                _ => {
+                    drop(Box::from_raw(value));
+                    -1
+                }
+            }
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _insert_checked!(i8; I8),
+        PyDictK::I16 => _insert_checked!(i16; I16),
+        PyDictK::I32 => _insert_checked!(i32; I32),
+        PyDictK::I64 => _insert_checked!(i64; I64),
+        PyDictK::U8 => _insert_checked!(u8; U8),
+        PyDictK::U16 => _insert_checked!(u16; U16),
+        PyDictK::U32 => _insert_checked!(u32; U32),
+        PyDictK::U64 => _insert_checked!(u64; U64),
+        PyDictK::PyString => _insert_checked!(PyString; PyString),
+        PyDictK::PyBool => _insert_checked!(PyBool; PyBool),
+        PyDictK::F64 => {
+            match *(Box::from_raw(key)) {
+                PyArg::F64(k) => {
+                    let dict = &mut *(dict as *mut PyDict<PyFloatKey, PyArg>);
+                    let value = *(Box::from_raw(value));
+                    dict.insert(PyFloatKey::from(k), value);
+                    0
+                }
+                _ => {
+                    drop(Box::from_raw(value));
+                    -1
+                }
+            }
+        }
+        PyDictK::Tuple => {
+            match *(Box::from_raw(key)) {
+                PyArg::PyTuple(k) => {
+                    let dict = &mut *(dict as *mut PyDict<PyTupleKey, PyArg>);
+                    let value = *(Box::from_raw(value));
+                    let k = Rc::try_unwrap(k).unwrap_or_else(|rc| (*rc).clone());
+                    dict.insert(PyTupleKey::from_pytuple(k), value);
+                    0
+                }
+                _ => {
+                    drop(Box::from_raw(value));
+                    -1
+                }
+            }
+        }
+    }
+}
+
+/// Removes a key from a live FFI-owned `PyDict`, returning the removed value (or null if the
+/// key wasn't present) instead of requiring the whole dict to be torn down and rebuilt just to
+/// drop one entry.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_remove(dict: *mut size_t,
+                                       k_type: &PyDictK,
+                                       key: *mut size_t)
+                                       -> *mut size_t {
+    macro_rules! _match_pyarg_out {
+        ($p:ident) => {{
+            match $p {
+                PyArg::I64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::F32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::F64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyBool(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyString(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyTuple(val) => {
+                    let owned = Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone());
+                    Box::into_raw(Box::new(owned)) as *mut size_t
+                },
+                PyArg::PyList(val) => {
+                    let owned = Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone());
+                    Box::into_raw(Box::new(owned)) as *mut size_t
+                },
+                _ => { _get_null() as *mut size_t },
+            }
+        }};
+    }
+    fn _get_null() -> *mut PyArg {
+        let p: *const PyArg = ptr::null();
+        p as *mut PyArg
     };
+    match *(k_type) {
+        PyDictK::I8 => {
+            let mut dict = &mut *(dict as *mut PyDict<i8, PyArg>);
+            let key = *(Box::from_raw(key as *mut i8));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::I16 => {
+            let mut dict = &mut *(dict as *mut PyDict<i16, PyArg>);
+            let key = *(Box::from_raw(key as *mut i16));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::I32 => {
+            let mut dict = &mut *(dict as *mut PyDict<i32, PyArg>);
+            let key = *(Box::from_raw(key as *mut i32));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::I64 => {
+            let mut dict = &mut *(dict as *mut PyDict<i64, PyArg>);
+            let key = *(Box::from_raw(key as *mut i64));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::U8 => {
+            let mut dict = &mut *(dict as *mut PyDict<u8, PyArg>);
+            let key = *(Box::from_raw(key as *mut u8));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::U16 => {
+            let mut dict = &mut *(dict as *mut PyDict<u16, PyArg>);
+            let key = *(Box::from_raw(key as *mut u16));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::U32 => {
+            let mut dict = &mut *(dict as *mut PyDict<u32, PyArg>);
+            let key = *(Box::from_raw(key as *mut u32));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::U64 => {
+            let mut dict = &mut *(dict as *mut PyDict<u64, PyArg>);
+            let key = *(Box::from_raw(key as *mut u64));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::PyString => {
+            let mut dict = &mut *(dict as *mut PyDict<PyString, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyString));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::PyBool => {
+            let mut dict = &mut *(dict as *mut PyDict<PyBool, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyBool));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::F64 => {
+            let mut dict = &mut *(dict as *mut PyDict<PyFloatKey, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyFloatKey));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::Tuple => {
+            let mut dict = &mut *(dict as *mut PyDict<PyTupleKey, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyTupleKey));
+            match dict.remove(&key) {
+                Some(val) => _match_pyarg_out!(val),
+                None => _get_null() as *mut size_t,
+            }
+        }
+    }
+}
+
+/// Returns `1` if `dict` has an entry for `key`, `0` otherwise. Unlike
+/// [pydict_get_element](fn.pydict_get_element.html), never allocates a value to hand back.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_contains(dict: *mut size_t,
+                                         k_type: &PyDictK,
+                                         key: *mut size_t)
+                                         -> i32 {
+    macro_rules! _contains {
+        ($kt:ty) => {{
+            let mut dict = &mut *(dict as *mut PyDict<$kt, PyArg>);
+            let key = *(Box::from_raw(key as *mut $kt));
+            if dict.contains_key(&key) { 1 } else { 0 }
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _contains!(i8),
+        PyDictK::I16 => _contains!(i16),
+        PyDictK::I32 => _contains!(i32),
+        PyDictK::I64 => _contains!(i64),
+        PyDictK::U8 => _contains!(u8),
+        PyDictK::U16 => _contains!(u16),
+        PyDictK::U32 => _contains!(u32),
+        PyDictK::U64 => _contains!(u64),
+        PyDictK::PyString => _contains!(PyString),
+        PyDictK::PyBool => _contains!(PyBool),
+        PyDictK::F64 => _contains!(PyFloatKey),
+        PyDictK::Tuple => _contains!(PyTupleKey),
+    }
+}
+
+/// Removes every key-value pair from `dict`, keeping the dict itself (and its allocated
+/// memory) alive for reuse rather than freeing and recreating it.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_clear(dict: *mut size_t, k_type: &PyDictK) {
+    match *(k_type) {
+        PyDictK::I8 => (&mut *(dict as *mut PyDict<i8, PyArg>)).clear(),
+        PyDictK::I16 => (&mut *(dict as *mut PyDict<i16, PyArg>)).clear(),
+        PyDictK::I32 => (&mut *(dict as *mut PyDict<i32, PyArg>)).clear(),
+        PyDictK::I64 => (&mut *(dict as *mut PyDict<i64, PyArg>)).clear(),
+        PyDictK::U8 => (&mut *(dict as *mut PyDict<u8, PyArg>)).clear(),
+        PyDictK::U16 => (&mut *(dict as *mut PyDict<u16, PyArg>)).clear(),
+        PyDictK::U32 => (&mut *(dict as *mut PyDict<u32, PyArg>)).clear(),
+        PyDictK::U64 => (&mut *(dict as *mut PyDict<u64, PyArg>)).clear(),
+        PyDictK::PyString => (&mut *(dict as *mut PyDict<PyString, PyArg>)).clear(),
+        PyDictK::PyBool => (&mut *(dict as *mut PyDict<PyBool, PyArg>)).clear(),
+        PyDictK::F64 => (&mut *(dict as *mut PyDict<PyFloatKey, PyArg>)).clear(),
+        PyDictK::Tuple => (&mut *(dict as *mut PyDict<PyTupleKey, PyArg>)).clear(),
+    }
+}
+
+#[test]
+fn mutate_dict_in_place() {
+    unsafe {
+        let mut hm = HashMap::new();
+        hm.insert(0u16, PyArg::PyString(PyString::from("zero")));
+        let dict = PyDict::from_iter(hm).as_ptr() as *mut size_t;
+        let k_type = PyDictK::U16;
+
+        assert_eq!(pydict_contains(dict, &k_type, Box::into_raw(Box::new(0u16)) as *mut size_t), 1);
+        assert_eq!(pydict_contains(dict, &k_type, Box::into_raw(Box::new(1u16)) as *mut size_t), 0);
+
+        let removed = pydict_remove(dict, &k_type, Box::into_raw(Box::new(0u16)) as *mut size_t);
+        assert!(!removed.is_null());
+        let removed: PyString = *(Box::from_raw(removed as *mut PyString));
+        assert_eq!(removed, PyString::from("zero"));
+        assert_eq!(pydict_contains(dict, &k_type, Box::into_raw(Box::new(0u16)) as *mut size_t), 0);
+
+        let key_ins = Box::into_raw(Box::new(PyArg::U16(2))) as *mut PyArg;
+        let val_ins = Box::into_raw(Box::new(PyArg::PyString(PyString::from("two")))) as *mut PyArg;
+        pydict_insert(dict, &k_type, key_ins, val_ins);
+        assert_eq!(pydict_contains(dict, &k_type, Box::into_raw(Box::new(2u16)) as *mut size_t), 1);
+
+        pydict_clear(dict, &k_type);
+        assert_eq!(pydict_contains(dict, &k_type, Box::into_raw(Box::new(2u16)) as *mut size_t), 0);
+
+        pydict_free(dict, &k_type);
+    }
 }
 
 #[test]
@@ -451,11 +972,9 @@ fn drain_dict() {
         let e1 = pydict_drain_element(iter, &k_type);
         assert!(!e1.is_null());
         let e1: &PyTuple = &*(e1 as *const PyTuple);
-        let v = match e1.next {
-            Some(ref v) => &(v.elem),
-            _ => panic!(),
-        };
-        if e1.elem == PyArg::U16(0) {
+        let k = e1.as_ref(0).unwrap();
+        let v = e1.as_ref(1).unwrap();
+        if k == &PyArg::U16(0) {
             assert_eq!(v, &PyArg::PyString(PyString::from("zero")));
         } else {
             assert_eq!(v, &PyArg::PyString(PyString::from("one")));
@@ -464,11 +983,9 @@ fn drain_dict() {
         let e2 = pydict_drain_element(iter, &k_type);
         assert!(!e2.is_null());
         let e2: &PyTuple = &*(e2 as *const PyTuple);
-        let v = match e2.next {
-            Some(ref v) => &(v.elem),
-            _ => panic!(),
-        };
-        if e2.elem == PyArg::U16(0) {
+        let k = e2.as_ref(0).unwrap();
+        let v = e2.as_ref(1).unwrap();
+        if k == &PyArg::U16(0) {
             assert_eq!(v, &PyArg::PyString(PyString::from("zero")));
         } else {
             assert_eq!(v, &PyArg::PyString(PyString::from("one")));
@@ -522,20 +1039,19 @@ pub unsafe extern "C" fn pydict_get_drain(dict: *mut size_t, k_type: &PyDictK) -
             let mut dict = &mut *(dict as *mut PyDict<PyBool, PyArg>);
             Box::into_raw(Box::new(dict.drain())) as *mut size_t
         }
+        PyDictK::F64 => {
+            let mut dict = &mut *(dict as *mut PyDict<PyFloatKey, PyArg>);
+            Box::into_raw(Box::new(dict.drain())) as *mut size_t
+        }
+        PyDictK::Tuple => {
+            let mut dict = &mut *(dict as *mut PyDict<PyTupleKey, PyArg>);
+            Box::into_raw(Box::new(dict.drain())) as *mut size_t
+        }
     }
 }
 
 fn kv_return_tuple(k: PyArg, v: PyArg) -> *mut PyTuple {
-    let ret = PyTuple {
-        elem: k,
-        idx: 0_usize,
-        next: Some(Box::new(PyTuple {
-            elem: v,
-            idx: 1_usize,
-            next: None,
-        })),
-    };
-    Box::into_raw(Box::new(ret))
+    PyTuple::from_vec(vec![k, v]).as_ptr()
 }
 
 #[no_mangle]
@@ -615,47 +1131,242 @@ pub unsafe extern "C" fn pydict_drain_element(iter: *mut size_t, k_type: &PyDict
                 None => _get_null(),
             }
         }
+        PyDictK::F64 => {
+            let mut iter = &mut *(iter as *mut Drain<PyFloatKey, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::F64(f64::from(val.0)), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::Tuple => {
+            let mut iter = &mut *(iter as *mut Drain<PyTupleKey, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::PyTuple(Rc::new(val.0.into_pytuple())), val.1),
+                None => _get_null(),
+            }
+        }
     }
 }
 
+/// Returns the number of key-value pairs in `dict`.
 #[no_mangle]
-pub unsafe extern "C" fn pydict_get_element(dict: *mut size_t,
-                                            k_type: &PyDictK,
-                                            key: *mut size_t)
-                                            -> *mut size_t {
-    macro_rules! _match_pyarg_out {
-        ($p:ident) => {{
-            fn _get_null() -> *mut PyArg {
-                let p: *const PyArg = ptr::null();
-                p as *mut PyArg
-            }
-            match $p {
-                PyArg::I64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::I32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::I16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::I8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::U32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::U16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::U8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::F32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::F64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::PyBool(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::PyString(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
-                PyArg::PyTuple(val) => { Box::into_raw(val) as *mut size_t },
-                PyArg::PyList(val) => { Box::into_raw(val) as *mut size_t },
-                _ => { _get_null() as *mut size_t },
-            }
-        }};
+pub unsafe extern "C" fn pydict_len(dict: *mut size_t, k_type: &PyDictK) -> size_t {
+    macro_rules! _len {
+        ($kt:ty) => {{ (&*(dict as *const PyDict<$kt, PyArg>)).len() }};
     }
-    fn _get_null() -> *mut PyArg {
-        let p: *const PyArg = ptr::null();
-        p as *mut PyArg
-    };
     match *(k_type) {
-        PyDictK::I8 => {
-            let mut dict = &mut *(dict as *mut PyDict<i8, PyArg>);
-            let key = *(Box::from_raw(key as *mut i8));
-            match dict.get(&key) {
+        PyDictK::I8 => _len!(i8),
+        PyDictK::I16 => _len!(i16),
+        PyDictK::I32 => _len!(i32),
+        PyDictK::I64 => _len!(i64),
+        PyDictK::U8 => _len!(u8),
+        PyDictK::U16 => _len!(u16),
+        PyDictK::U32 => _len!(u32),
+        PyDictK::U64 => _len!(u64),
+        PyDictK::PyString => _len!(PyString),
+        PyDictK::PyBool => _len!(PyBool),
+        PyDictK::F64 => _len!(PyFloatKey),
+        PyDictK::Tuple => _len!(PyTupleKey),
+    }
+}
+
+/// Takes full ownership of `dict` and returns an opaque cursor over its (key, value) pairs,
+/// for rebuilding a whole Python dict from a Rust-produced one when the keys aren't known
+/// ahead of time. Unlike [pydict_get_drain](fn.pydict_get_drain.html) (which drains a dict
+/// in place, leaving the emptied dict itself still alive and owned by the caller), `dict`
+/// must not be used again after this call — walk it to completion with
+/// [pydict_iter_next](fn.pydict_iter_next.html) and release it with
+/// [pydict_iter_free](fn.pydict_iter_free.html).
+#[no_mangle]
+pub unsafe extern "C" fn pydict_iter_new(dict: *mut size_t, k_type: &PyDictK) -> *mut size_t {
+    macro_rules! _iter_new {
+        ($kt:ty) => {{
+            let dict = PyDict::<$kt, PyArg>::from_ptr(dict);
+            Box::into_raw(Box::new(dict.table.into_iter())) as *mut size_t
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _iter_new!(i8),
+        PyDictK::I16 => _iter_new!(i16),
+        PyDictK::I32 => _iter_new!(i32),
+        PyDictK::I64 => _iter_new!(i64),
+        PyDictK::U8 => _iter_new!(u8),
+        PyDictK::U16 => _iter_new!(u16),
+        PyDictK::U32 => _iter_new!(u32),
+        PyDictK::U64 => _iter_new!(u64),
+        PyDictK::PyString => _iter_new!(PyString),
+        PyDictK::PyBool => _iter_new!(PyBool),
+        PyDictK::F64 => _iter_new!(PyFloatKey),
+        PyDictK::Tuple => _iter_new!(PyTupleKey),
+    }
+}
+
+/// Yields the next `(key, value)` pair from a [pydict_iter_new](fn.pydict_iter_new.html)
+/// cursor as a `PyTuple`, or a null pointer once the cursor is exhausted.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_iter_next(iter: *mut size_t, k_type: &PyDictK) -> *mut PyTuple {
+    fn _get_null() -> *mut PyTuple {
+        let p: *const PyTuple = ptr::null();
+        p as *mut PyTuple
+    }
+    match *(k_type) {
+        PyDictK::I8 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<i8, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::I8(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::I16 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<i16, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::I16(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::I32 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<i32, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::I32(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::I64 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<i64, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::I64(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::U8 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<u8, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::U8(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::U16 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<u16, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::U16(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::U32 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<u32, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::U32(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::U64 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<u64, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::U64(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::PyString => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<PyString, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::PyString(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::PyBool => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<PyBool, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::PyBool(val.0), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::F64 => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<PyFloatKey, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::F64(f64::from(val.0)), val.1),
+                None => _get_null(),
+            }
+        }
+        PyDictK::Tuple => {
+            let iter = &mut *(iter as *mut HashMapIntoIter<PyTupleKey, PyArg>);
+            match iter.next() {
+                Some(val) => kv_return_tuple(PyArg::PyTuple(Rc::new(val.0.into_pytuple())), val.1),
+                None => _get_null(),
+            }
+        }
+    }
+}
+
+/// Releases a [pydict_iter_new](fn.pydict_iter_new.html) cursor. Safe to call once the
+/// cursor has been walked to exhaustion, or to abandon it early.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_iter_free(iter: *mut size_t, k_type: &PyDictK) {
+    if iter.is_null() {
+        return;
+    }
+    macro_rules! _iter_free {
+        ($kt:ty) => {{ drop(Box::from_raw(iter as *mut HashMapIntoIter<$kt, PyArg>)); }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _iter_free!(i8),
+        PyDictK::I16 => _iter_free!(i16),
+        PyDictK::I32 => _iter_free!(i32),
+        PyDictK::I64 => _iter_free!(i64),
+        PyDictK::U8 => _iter_free!(u8),
+        PyDictK::U16 => _iter_free!(u16),
+        PyDictK::U32 => _iter_free!(u32),
+        PyDictK::U64 => _iter_free!(u64),
+        PyDictK::PyString => _iter_free!(PyString),
+        PyDictK::PyBool => _iter_free!(PyBool),
+        PyDictK::F64 => _iter_free!(PyFloatKey),
+        PyDictK::Tuple => _iter_free!(PyTupleKey),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pydict_get_element(dict: *mut size_t,
+                                            k_type: &PyDictK,
+                                            key: *mut size_t)
+                                            -> *mut size_t {
+    macro_rules! _match_pyarg_out {
+        ($p:ident) => {{
+            fn _get_null() -> *mut PyArg {
+                let p: *const PyArg = ptr::null();
+                p as *mut PyArg
+            }
+            match $p {
+                PyArg::I64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::I8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U16(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::U8(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::F32(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::F64(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyBool(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyString(val) => { Box::into_raw(Box::new(val)) as *mut size_t },
+                PyArg::PyTuple(val) => {
+                    let owned = Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone());
+                    Box::into_raw(Box::new(owned)) as *mut size_t
+                },
+                PyArg::PyList(val) => {
+                    let owned = Rc::try_unwrap(val).unwrap_or_else(|val| (*val).clone());
+                    Box::into_raw(Box::new(owned)) as *mut size_t
+                },
+                _ => { _get_null() as *mut size_t },
+            }
+        }};
+    }
+    fn _get_null() -> *mut PyArg {
+        let p: *const PyArg = ptr::null();
+        p as *mut PyArg
+    };
+    match *(k_type) {
+        PyDictK::I8 => {
+            let mut dict = &mut *(dict as *mut PyDict<i8, PyArg>);
+            let key = *(Box::from_raw(key as *mut i8));
+            match dict.get(&key) {
                 Some(ref val) => {
                     let v = (*val).clone();
                     _match_pyarg_out!(v)
@@ -762,6 +1473,132 @@ pub unsafe extern "C" fn pydict_get_element(dict: *mut size_t,
                 None => _get_null() as *mut size_t,
             }
         }
+        PyDictK::F64 => {
+            let mut dict = &mut *(dict as *mut PyDict<PyFloatKey, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyFloatKey));
+            match dict.get(&key) {
+                Some(ref val) => {
+                    let v = (*val).clone();
+                    _match_pyarg_out!(v)
+                }
+                None => _get_null() as *mut size_t,
+            }
+        }
+        PyDictK::Tuple => {
+            let mut dict = &mut *(dict as *mut PyDict<PyTupleKey, PyArg>);
+            let key = *(Box::from_raw(key as *mut PyTupleKey));
+            match dict.get(&key) {
+                Some(ref val) => {
+                    let v = (*val).clone();
+                    _match_pyarg_out!(v)
+                }
+                None => _get_null() as *mut size_t,
+            }
+        }
+    }
+}
+
+/// Non-aborting sibling of [pydict_get_element](fn.pydict_get_element.html). Rather than
+/// returning a null pointer indistinguishably for both "key not found" and "dict was null",
+/// writes the looked-up value through `out` and reports which case happened via the return
+/// code: `0` on success, `1` if `key` is not present or its value is a `PyDict`/`None` (for
+/// which `pydict_get_element` hands back a null pointer too), `-2` if `dict` is null. There is no `-1`
+/// (key-type mismatch) case here, unlike [pydict_insert_checked](fn.pydict_insert_checked.html):
+/// `key` is already a raw `*mut $kt` pointer of the dict's own key type, not a `PyArg`, so there
+/// is no variant to mismatch against.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_get_element_checked(dict: *mut size_t,
+                                                     k_type: &PyDictK,
+                                                     key: *mut size_t,
+                                                     out: *mut *mut size_t)
+                                                     -> i32 {
+    if dict.is_null() {
+        return -2;
+    }
+    let result = pydict_get_element(dict, k_type, key);
+    if result.is_null() {
+        1
+    } else {
+        *out = result;
+        0
+    }
+}
+
+/// Reads an element without forcing a deep copy, unlike [pydict_get_element](fn.pydict_get_element.html)
+/// (which hands back a naked `$kt`/`PyArg`-payload pointer, unwrapping any `PyTuple`/`PyList` into
+/// a uniquely-owned value along the way). Instead returns a `*mut PyArg` whose `PyTuple`/`PyList`
+/// payload, if any, shares its backing storage with the value still held by `dict` — cloning it
+/// is an `Rc` refcount bump, not a structural copy. Use
+/// [pyarg_clone_into_owned](../fn.pyarg_clone_into_owned.html) if the caller actually needs an
+/// independent, mutable copy. Returns a null pointer if `key` is not present.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_get_element_shared(dict: *mut size_t,
+                                                    k_type: &PyDictK,
+                                                    key: *mut size_t)
+                                                    -> *mut PyArg {
+    macro_rules! _get_shared {
+        ($kt:ty) => {{
+            let dict = &mut *(dict as *mut PyDict<$kt, PyArg>);
+            let key = *(Box::from_raw(key as *mut $kt));
+            match dict.get(&key) {
+                Some(val) => Box::into_raw(Box::new(val.clone())),
+                None => ptr::null_mut(),
+            }
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _get_shared!(i8),
+        PyDictK::I16 => _get_shared!(i16),
+        PyDictK::I32 => _get_shared!(i32),
+        PyDictK::I64 => _get_shared!(i64),
+        PyDictK::U8 => _get_shared!(u8),
+        PyDictK::U16 => _get_shared!(u16),
+        PyDictK::U32 => _get_shared!(u32),
+        PyDictK::U64 => _get_shared!(u64),
+        PyDictK::PyString => _get_shared!(PyString),
+        PyDictK::PyBool => _get_shared!(PyBool),
+        PyDictK::F64 => _get_shared!(PyFloatKey),
+        PyDictK::Tuple => _get_shared!(PyTupleKey),
+    }
+}
+
+/// Reads an element by reference with no clone and no allocation at all, unlike
+/// [pydict_get_element_shared](fn.pydict_get_element_shared.html) (still clones the outer
+/// `PyArg`, even if a nested `PyTuple`/`PyList` payload is just an `Rc` bump). Returns a raw
+/// pointer straight into the value still stored in `dict`; it must not be freed by the caller,
+/// and is valid only until the next mutation (`pydict_insert`/`pydict_remove`/`pydict_clear`)
+/// or free of `dict`. Intended for throughput-sensitive, read-only traversal; use
+/// [pydict_get_element](fn.pydict_get_element.html)/
+/// [pydict_get_element_shared](fn.pydict_get_element_shared.html) when the caller needs
+/// ownership. Returns a null pointer if `key` is not present.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_borrow_pyarg(dict: *mut size_t,
+                                             k_type: &PyDictK,
+                                             key: *mut size_t)
+                                             -> *const PyArg {
+    macro_rules! _borrow {
+        ($kt:ty) => {{
+            let dict = &mut *(dict as *mut PyDict<$kt, PyArg>);
+            let key = *(Box::from_raw(key as *mut $kt));
+            match dict.get(&key) {
+                Some(val) => val as *const PyArg,
+                None => ptr::null(),
+            }
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _borrow!(i8),
+        PyDictK::I16 => _borrow!(i16),
+        PyDictK::I32 => _borrow!(i32),
+        PyDictK::I64 => _borrow!(i64),
+        PyDictK::U8 => _borrow!(u8),
+        PyDictK::U16 => _borrow!(u16),
+        PyDictK::U32 => _borrow!(u32),
+        PyDictK::U64 => _borrow!(u64),
+        PyDictK::PyString => _borrow!(PyString),
+        PyDictK::PyBool => _borrow!(PyBool),
+        PyDictK::F64 => _borrow!(PyFloatKey),
+        PyDictK::Tuple => _borrow!(PyTupleKey),
     }
 }
 
@@ -801,10 +1638,129 @@ pub unsafe extern "C" fn pydict_free(dict: *mut size_t, k_type: &PyDictK) {
         PyDictK::PyBool => {
             Box::from_raw(dict as *mut PyDict<PyBool, PyArg>);
         }
+        PyDictK::F64 => {
+            Box::from_raw(dict as *mut PyDict<PyFloatKey, PyArg>);
+        }
+        PyDictK::Tuple => {
+            Box::from_raw(dict as *mut PyDict<PyTupleKey, PyArg>);
+        }
+    }
+}
+
+/// A PyDict key wrapping `f64`. `f64` itself has no total `Eq`/`Hash` (NaN isn't reflexively
+/// equal under IEEE 754), so this hashes and compares by the value's raw bits instead, with every
+/// NaN bit pattern canonicalized to one representative so distinct NaNs still agree with each
+/// other as a key, the same convention dict keys in typed Python tooling settle on for floats.
+#[derive(Clone, Copy, Debug)]
+pub struct PyFloatKey(u64);
+
+impl PyFloatKey {
+    fn canonical_bits(v: f64) -> u64 {
+        if v.is_nan() { 0x7ff8_0000_0000_0000 } else { v.to_bits() }
+    }
+}
+
+impl From<f64> for PyFloatKey {
+    fn from(v: f64) -> PyFloatKey {
+        PyFloatKey(PyFloatKey::canonical_bits(v))
+    }
+}
+
+impl From<PyFloatKey> for f64 {
+    fn from(k: PyFloatKey) -> f64 {
+        f64::from_bits(k.0)
+    }
+}
+
+impl PartialEq for PyFloatKey {
+    fn eq(&self, other: &PyFloatKey) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for PyFloatKey {}
+
+impl ::std::hash::Hash for PyFloatKey {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// One element of a [PyTupleKey](struct.PyTupleKey.html) composite key — the same scalar key
+/// types `PyDictK` allows on their own, `Tuple` itself excluded since composite keys don't nest.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PyKeyPart {
+    I64(i64),
+    I32(i32),
+    I16(i16),
+    I8(i8),
+    U64(u64),
+    U32(u32),
+    U16(u16),
+    U8(u8),
+    F64(PyFloatKey),
+    PyBool(PyBool),
+    PyString(PyString),
+}
+
+impl PyKeyPart {
+    fn from_pyarg(v: PyArg) -> PyKeyPart {
+        match v {
+            PyArg::I64(v) => PyKeyPart::I64(v),
+            PyArg::I32(v) => PyKeyPart::I32(v),
+            PyArg::I16(v) => PyKeyPart::I16(v),
+            PyArg::I8(v) => PyKeyPart::I8(v),
+            PyArg::U64(v) => PyKeyPart::U64(v),
+            PyArg::U32(v) => PyKeyPart::U32(v),
+            PyArg::U16(v) => PyKeyPart::U16(v),
+            PyArg::U8(v) => PyKeyPart::U8(v),
+            PyArg::F64(v) => PyKeyPart::F64(PyFloatKey::from(v)),
+            PyArg::PyBool(v) => PyKeyPart::PyBool(v),
+            PyArg::PyString(v) => PyKeyPart::PyString(v),
+            _ => _rustypy_abort_xtract_fail!("unsupported PyArg variant as a PyDict tuple \
+                                              key part"),
+        }
+    }
+
+    fn into_pyarg(self) -> PyArg {
+        match self {
+            PyKeyPart::I64(v) => PyArg::I64(v),
+            PyKeyPart::I32(v) => PyArg::I32(v),
+            PyKeyPart::I16(v) => PyArg::I16(v),
+            PyKeyPart::I8(v) => PyArg::I8(v),
+            PyKeyPart::U64(v) => PyArg::U64(v),
+            PyKeyPart::U32(v) => PyArg::U32(v),
+            PyKeyPart::U16(v) => PyArg::U16(v),
+            PyKeyPart::U8(v) => PyArg::U8(v),
+            PyKeyPart::F64(v) => PyArg::F64(f64::from(v)),
+            PyKeyPart::PyBool(v) => PyArg::PyBool(v),
+            PyKeyPart::PyString(v) => PyArg::PyString(v),
+        }
+    }
+}
+
+/// A composite PyDict key built from a fixed sequence of scalar
+/// [PyKeyPart](enum.PyKeyPart.html)s, mirroring a Python tuple-of-scalars key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PyTupleKey(Vec<PyKeyPart>);
+
+impl PyTupleKey {
+    fn from_pytuple(t: PyTuple) -> PyTupleKey {
+        let len = t.len();
+        let mut t = t;
+        let parts = (0..len)
+            .map(|i| PyKeyPart::from_pyarg(t.replace_elem(i).unwrap()))
+            .collect();
+        PyTupleKey(parts)
+    }
+
+    fn into_pytuple(self) -> PyTuple {
+        PyTuple::from_vec(self.0.into_iter().map(PyKeyPart::into_pyarg).collect())
     }
 }
 
 /// Types allowed as PyDict key values.
+#[derive(Clone, Copy)]
 pub enum PyDictK {
     I64,
     I32,
@@ -814,13 +1770,16 @@ pub enum PyDictK {
     U32,
     U16,
     U8,
+    F64,
     PyBool,
     PyString,
+    Tuple,
 }
 
 mod key_bound {
     use pytypes::pystring::PyString;
     use pytypes::pybool::PyBool;
+    use super::{PyFloatKey, PyTupleKey};
 
     pub trait PyDictKey {}
     impl PyDictKey for i64 {}
@@ -833,6 +1792,8 @@ mod key_bound {
     impl PyDictKey for u8 {}
     impl PyDictKey for PyString {}
     impl PyDictKey for PyBool {}
+    impl PyDictKey for PyFloatKey {}
+    impl PyDictKey for PyTupleKey {}
 }
 
 #[no_mangle]
@@ -846,8 +1807,561 @@ pub extern "C" fn pydict_get_key_type(k: u32) -> *mut PyDictK {
         6 => Box::into_raw(Box::new(PyDictK::U32)),
         7 => Box::into_raw(Box::new(PyDictK::I64)),
         8 => Box::into_raw(Box::new(PyDictK::U64)),
+        9 => Box::into_raw(Box::new(PyDictK::F64)),
         11 => Box::into_raw(Box::new(PyDictK::PyBool)),
         12 => Box::into_raw(Box::new(PyDictK::PyString)),
+        13 => Box::into_raw(Box::new(PyDictK::Tuple)),
         _ => _rustypy_abort_xtract_fail!("type not supported as PyDict key type"),
     }
 }
+
+/// Non-aborting sibling of [pydict_get_key_type](fn.pydict_get_key_type.html): instead of
+/// killing the process on an unrecognized discriminant, writes the resolved `PyDictK` through
+/// `out` and returns `0`. Leaves `out` null and returns `-1` for an unsupported `k`, so a Python
+/// wrapper can turn that into a `TypeError` instead of a crash. The `PyDictK` handed back can be
+/// passed straight into `pydict_get_element`/`pydict_free`/etc. exactly like the unchecked path.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_get_key_type_checked(k: u32, out: *mut *mut PyDictK) -> i32 {
+    let resolved = match k {
+        1 => PyDictK::U8,
+        2 => PyDictK::I8,
+        3 => PyDictK::I16,
+        4 => PyDictK::U16,
+        5 => PyDictK::I32,
+        6 => PyDictK::U32,
+        7 => PyDictK::I64,
+        8 => PyDictK::U64,
+        9 => PyDictK::F64,
+        11 => PyDictK::PyBool,
+        12 => PyDictK::PyString,
+        13 => PyDictK::Tuple,
+        _ => {
+            *out = ptr::null_mut();
+            return -1;
+        }
+    };
+    *out = Box::into_raw(Box::new(resolved));
+    0
+}
+
+/// Application-defined CBOR tag (outside the IANA-assigned range) marking an array as a
+/// `PyTuple` rather than a plain `PyList` when (de)serializing through
+/// [pydict_to_cbor](fn.pydict_to_cbor.html)/[pydict_from_cbor](fn.pydict_from_cbor.html).
+const CBOR_TUPLE_TAG: u64 = 121;
+
+/// Application-defined CBOR tags marking which non-`i64` `PyArg` integer variant a
+/// `CborValue::Integer` came from. CBOR's own integer representation carries a value but not
+/// Rust's width/signedness, so without these every integer `PyArg` would decode back as
+/// `PyArg::I64` regardless of which variant it was encoded from - and a `PyArg::U64` above
+/// `i64::MAX` would silently wrap instead of round-tripping intact. `I64` itself is left
+/// untagged, since it's the default integer type on the wire.
+const CBOR_I8_TAG: u64 = 122;
+const CBOR_I16_TAG: u64 = 123;
+const CBOR_I32_TAG: u64 = 124;
+const CBOR_U8_TAG: u64 = 125;
+const CBOR_U16_TAG: u64 = 126;
+const CBOR_U32_TAG: u64 = 127;
+const CBOR_U64_TAG: u64 = 128;
+
+/// Owned byte buffer returned by [pydict_to_cbor](fn.pydict_to_cbor.html), so the CBOR-encoded
+/// bytes can cross the FFI boundary as a single `(ptr, len)` pair. Release it with
+/// [pydict_cbor_free](fn.pydict_cbor_free.html).
+#[repr(C)]
+pub struct CborBuf {
+    pub ptr: *mut u8,
+    pub len: size_t,
+}
+
+impl From<Vec<u8>> for CborBuf {
+    fn from(mut bytes: Vec<u8>) -> CborBuf {
+        bytes.shrink_to_fit();
+        let ptr = bytes.as_mut_ptr();
+        let len = bytes.len();
+        mem::forget(bytes);
+        CborBuf { ptr, len }
+    }
+}
+
+/// Consumes a `PyArg` and encodes it as a `serde_cbor::Value`. Scalars map to the obvious CBOR
+/// primitive; `PyTuple` is wrapped in `CBOR_TUPLE_TAG` so it round-trips as a fixed-arity tuple
+/// instead of a plain `PyList`; a nested `PyArg::PyDict` recurses assuming it shares `k_type`
+/// with its parent (see the module docs for why).
+fn pyarg_into_cbor(e: PyArg, k_type: &PyDictK) -> CborValue {
+    match e {
+        PyArg::I64(v) => CborValue::Integer(v as i128),
+        PyArg::I32(v) => CborValue::Tag(CBOR_I32_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::I16(v) => CborValue::Tag(CBOR_I16_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::I8(v) => CborValue::Tag(CBOR_I8_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::U64(v) => CborValue::Tag(CBOR_U64_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::U32(v) => CborValue::Tag(CBOR_U32_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::U16(v) => CborValue::Tag(CBOR_U16_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::U8(v) => CborValue::Tag(CBOR_U8_TAG, Box::new(CborValue::Integer(v as i128))),
+        PyArg::F32(v) => CborValue::Float(v as f64),
+        PyArg::F64(v) => CborValue::Float(v),
+        PyArg::PyBool(v) => CborValue::Bool(v.to_bool()),
+        PyArg::PyString(v) => CborValue::Text(v.to_string()),
+        PyArg::PyTuple(v) => {
+            let mut v = Rc::try_unwrap(v).unwrap_or_else(|v| (*v).clone());
+            let len = v.len();
+            let mut elems = Vec::with_capacity(len);
+            for i in 0..len {
+                elems.push(pyarg_into_cbor(v.replace_elem(i).unwrap(), k_type));
+            }
+            CborValue::Tag(CBOR_TUPLE_TAG, Box::new(CborValue::Array(elems)))
+        }
+        PyArg::PyList(v) => {
+            let mut v = Rc::try_unwrap(v).unwrap_or_else(|v| (*v).clone());
+            let mut elems = VecDeque::with_capacity(v.len());
+            while let Some(next) = v.pop() {
+                elems.push_front(pyarg_into_cbor(next, k_type));
+            }
+            CborValue::Array(Vec::from(elems))
+        }
+        PyArg::PyDict(ptr) => unsafe { pydict_ptr_into_cbor_value(ptr, k_type) },
+        PyArg::None => CborValue::Null,
+    }
+}
+
+/// Drains a type-erased `*mut size_t` dict (as stored inside `PyArg::PyDict`) into a CBOR map,
+/// dispatching the concrete key type via `k_type` exactly as `pydict_new` does.
+unsafe fn pydict_ptr_into_cbor_value(ptr: *mut size_t, k_type: &PyDictK) -> CborValue {
+    macro_rules! _drain_to_map {
+        ($kt:ty; $key_to_cbor:expr) => {{
+            let mut dict: PyDict<$kt, PyArg> = PyDict::from_ptr(ptr);
+            let mut map = BTreeMap::new();
+            let key_to_cbor: fn($kt) -> CborValue = $key_to_cbor;
+            for (k, v) in dict.drain() {
+                map.insert(key_to_cbor(k), pyarg_into_cbor(v, k_type));
+            }
+            CborValue::Map(map)
+        }};
+    }
+    match *(k_type) {
+        PyDictK::I8 => _drain_to_map!(i8; |k| CborValue::Integer(k as i128)),
+        PyDictK::I16 => _drain_to_map!(i16; |k| CborValue::Integer(k as i128)),
+        PyDictK::I32 => _drain_to_map!(i32; |k| CborValue::Integer(k as i128)),
+        PyDictK::I64 => _drain_to_map!(i64; |k| CborValue::Integer(k as i128)),
+        PyDictK::U8 => _drain_to_map!(u8; |k| CborValue::Integer(k as i128)),
+        PyDictK::U16 => _drain_to_map!(u16; |k| CborValue::Integer(k as i128)),
+        PyDictK::U32 => _drain_to_map!(u32; |k| CborValue::Integer(k as i128)),
+        PyDictK::U64 => _drain_to_map!(u64; |k| CborValue::Integer(k as i128)),
+        PyDictK::PyString => _drain_to_map!(PyString; |k: PyString| CborValue::Text(k.to_string())),
+        PyDictK::PyBool => _drain_to_map!(PyBool; |k: PyBool| CborValue::Bool(k.to_bool())),
+        PyDictK::F64 => _drain_to_map!(PyFloatKey; |k: PyFloatKey| CborValue::Float(f64::from(k))),
+        PyDictK::Tuple => {
+            let mut dict: PyDict<PyTupleKey, PyArg> = PyDict::from_ptr(ptr);
+            let mut map = BTreeMap::new();
+            for (k, v) in dict.drain() {
+                let key_cbor = pyarg_into_cbor(PyArg::PyTuple(Rc::new(k.into_pytuple())), k_type);
+                map.insert(key_cbor, pyarg_into_cbor(v, k_type));
+            }
+            CborValue::Map(map)
+        }
+    }
+}
+
+/// Serializes an entire `PyDict` value tree into a single CBOR byte buffer, so Python can
+/// deserialize it in one call instead of draining element-by-element through
+/// `pydict_get_drain`/`pydict_drain_element`.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_to_cbor(dict: *mut size_t, k_type: &PyDictK) -> CborBuf {
+    let value = pydict_ptr_into_cbor_value(dict, k_type);
+    let bytes = serde_cbor::to_vec(&value).expect("failed to serialize a PyDict to CBOR");
+    CborBuf::from(bytes)
+}
+
+/// Inverse of [pyarg_into_cbor](fn.pyarg_into_cbor.html): reconstructs a `PyArg` from a decoded
+/// CBOR value. A bare `CborValue::Array` becomes a `PyList`; one wrapped in `CBOR_TUPLE_TAG`
+/// becomes a `PyTuple` instead, mirroring the distinction made while encoding.
+fn cbor_value_into_pyarg(v: CborValue, k_type: &PyDictK) -> PyArg {
+    match v {
+        CborValue::Integer(i) => PyArg::I64(i as i64),
+        CborValue::Float(f) => PyArg::F64(f),
+        CborValue::Bool(b) => PyArg::PyBool(PyBool::from(b)),
+        CborValue::Text(s) => PyArg::PyString(PyString::from(s)),
+        CborValue::Null => PyArg::None,
+        CborValue::Tag(CBOR_TUPLE_TAG, boxed) => {
+            match *boxed {
+                CborValue::Array(items) => {
+                    let elems = items.into_iter()
+                        .map(|i| cbor_value_into_pyarg(i, k_type))
+                        .collect();
+                    PyArg::PyTuple(Rc::new(PyTuple::from_vec(elems)))
+                }
+                _ => _rustypy_abort_xtract_fail!("a PyTuple CBOR tag must wrap an array"),
+            }
+        }
+        CborValue::Tag(tag, boxed) => {
+            let i = match *boxed {
+                CborValue::Integer(i) => i,
+                _ => _rustypy_abort_xtract_fail!("an integer-width CBOR tag must wrap an integer"),
+            };
+            match tag {
+                CBOR_I8_TAG => PyArg::I8(i as i8),
+                CBOR_I16_TAG => PyArg::I16(i as i16),
+                CBOR_I32_TAG => PyArg::I32(i as i32),
+                CBOR_U8_TAG => PyArg::U8(i as u8),
+                CBOR_U16_TAG => PyArg::U16(i as u16),
+                CBOR_U32_TAG => PyArg::U32(i as u32),
+                CBOR_U64_TAG => PyArg::U64(i as u64),
+                _ => _rustypy_abort_xtract_fail!("unsupported CBOR tag while decoding a PyDict"),
+            }
+        }
+        CborValue::Array(items) => {
+            let list = PyList::from_iter(
+                items.into_iter().map(|i| cbor_value_into_pyarg(i, k_type)),
+            );
+            PyArg::PyList(Rc::new(list))
+        }
+        CborValue::Map(map) => PyArg::PyDict(unsafe { cbor_map_into_pydict_ptr(map, k_type) }),
+        _ => _rustypy_abort_xtract_fail!("unsupported CBOR value while decoding a PyDict"),
+    }
+}
+
+/// Reconstructs a type-erased `*mut size_t` dict from a decoded CBOR map, dispatching the
+/// concrete key type via `k_type` exactly as `pydict_new` does.
+unsafe fn cbor_map_into_pydict_ptr(map: BTreeMap<CborValue, CborValue>,
+                                   k_type: &PyDictK)
+                                   -> *mut size_t {
+    macro_rules! _map_to_dict {
+        ($kt:ty; $key_from_cbor:expr) => {{
+            let mut dict: PyDict<$kt, PyArg> = PyDict::new();
+            let key_from_cbor: fn(CborValue) -> $kt = $key_from_cbor;
+            for (k, v) in map {
+                dict.insert(key_from_cbor(k), cbor_value_into_pyarg(v, k_type));
+            }
+            dict.as_ptr()
+        }};
+    }
+    macro_rules! _int_key {
+        ($kt:ty) => {
+            |k: CborValue| match k {
+                CborValue::Integer(i) => i as $kt,
+                _ => _rustypy_abort_xtract_fail!("expected an integer CBOR key"),
+            }
+        };
+    }
+    match *(k_type) {
+        PyDictK::I8 => _map_to_dict!(i8; _int_key!(i8)),
+        PyDictK::I16 => _map_to_dict!(i16; _int_key!(i16)),
+        PyDictK::I32 => _map_to_dict!(i32; _int_key!(i32)),
+        PyDictK::I64 => _map_to_dict!(i64; _int_key!(i64)),
+        PyDictK::U8 => _map_to_dict!(u8; _int_key!(u8)),
+        PyDictK::U16 => _map_to_dict!(u16; _int_key!(u16)),
+        PyDictK::U32 => _map_to_dict!(u32; _int_key!(u32)),
+        PyDictK::U64 => _map_to_dict!(u64; _int_key!(u64)),
+        PyDictK::PyString => _map_to_dict!(PyString; |k: CborValue| match k {
+            CborValue::Text(s) => PyString::from(s),
+            _ => _rustypy_abort_xtract_fail!("expected a text CBOR key"),
+        }),
+        PyDictK::PyBool => _map_to_dict!(PyBool; |k: CborValue| match k {
+            CborValue::Bool(b) => PyBool::from(b),
+            _ => _rustypy_abort_xtract_fail!("expected a boolean CBOR key"),
+        }),
+        PyDictK::F64 => _map_to_dict!(PyFloatKey; |k: CborValue| match k {
+            CborValue::Float(f) => PyFloatKey::from(f),
+            _ => _rustypy_abort_xtract_fail!("expected a float CBOR key"),
+        }),
+        PyDictK::Tuple => {
+            let mut dict: PyDict<PyTupleKey, PyArg> = PyDict::new();
+            for (k, v) in map {
+                let key = match cbor_value_into_pyarg(k, k_type) {
+                    PyArg::PyTuple(t) => {
+                        let t = Rc::try_unwrap(t).unwrap_or_else(|t| (*t).clone());
+                        PyTupleKey::from_pytuple(t)
+                    }
+                    _ => _rustypy_abort_xtract_fail!("expected a tuple CBOR key"),
+                };
+                dict.insert(key, cbor_value_into_pyarg(v, k_type));
+            }
+            dict.as_ptr()
+        }
+    }
+}
+
+/// Deserializes a single CBOR byte buffer (as produced by
+/// [pydict_to_cbor](fn.pydict_to_cbor.html)) back into a `PyDict` value tree.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_from_cbor(buf: *const u8, len: size_t, k_type: &PyDictK) -> *mut size_t {
+    let bytes = slice::from_raw_parts(buf, len);
+    let value: CborValue = serde_cbor::from_slice(bytes)
+        .expect("failed to deserialize a PyDict from a CBOR buffer");
+    match value {
+        CborValue::Map(map) => cbor_map_into_pydict_ptr(map, k_type),
+        _ => _rustypy_abort_xtract_fail!("CBOR buffer did not contain a top-level map"),
+    }
+}
+
+/// Frees a [CborBuf](struct.CborBuf.html) previously returned by
+/// [pydict_to_cbor](fn.pydict_to_cbor.html).
+#[no_mangle]
+pub unsafe extern "C" fn pydict_cbor_free(buf: CborBuf) {
+    if buf.ptr.is_null() {
+        return;
+    }
+    drop(Vec::from_raw_parts(buf.ptr, buf.len, buf.len));
+}
+
+#[test]
+fn cbor_roundtrip_preserves_integer_width() {
+    unsafe {
+        let mut hm = HashMap::new();
+        hm.insert(0u16, PyArg::U64(u64::max_value()));
+        hm.insert(1u16, PyArg::I8(-1));
+        hm.insert(2u16, PyArg::U8(255));
+        let dict = PyDict::from_iter(hm).as_ptr() as *mut size_t;
+        let k_type = PyDictK::U16;
+
+        let buf = pydict_to_cbor(dict, &k_type);
+        let restored = pydict_from_cbor(buf.ptr, buf.len, &k_type);
+        pydict_cbor_free(buf);
+
+        let mut restored: PyDict<u16, PyArg> = PyDict::from_ptr(restored);
+        assert_eq!(restored.get(&0u16), Some(&PyArg::U64(u64::max_value())));
+        assert_eq!(restored.get(&1u16), Some(&PyArg::I8(-1)));
+        assert_eq!(restored.get(&2u16), Some(&PyArg::U8(255)));
+    }
+}
+
+/// A `PyArg` variant with no further recursive structure — the leaves of a
+/// [PyShape](enum.PyShape.html) tree.
+pub enum PyArgKind {
+    I64,
+    I32,
+    I16,
+    I8,
+    U64,
+    U32,
+    U16,
+    U8,
+    F32,
+    F64,
+    PyBool,
+    PyString,
+    None,
+}
+
+impl PyArgKind {
+    fn repr(&self) -> &'static str {
+        match *self {
+            PyArgKind::I64 => "I64",
+            PyArgKind::I32 => "I32",
+            PyArgKind::I16 => "I16",
+            PyArgKind::I8 => "I8",
+            PyArgKind::U64 => "U64",
+            PyArgKind::U32 => "U32",
+            PyArgKind::U16 => "U16",
+            PyArgKind::U8 => "U8",
+            PyArgKind::F32 => "F32",
+            PyArgKind::F64 => "F64",
+            PyArgKind::PyBool => "PyBool",
+            PyArgKind::PyString => "PyString",
+            PyArgKind::None => "None",
+        }
+    }
+
+    fn matches(&self, v: &PyArg) -> bool {
+        match (self, v) {
+            (&PyArgKind::I64, &PyArg::I64(_)) => true,
+            (&PyArgKind::I32, &PyArg::I32(_)) => true,
+            (&PyArgKind::I16, &PyArg::I16(_)) => true,
+            (&PyArgKind::I8, &PyArg::I8(_)) => true,
+            (&PyArgKind::U64, &PyArg::U64(_)) => true,
+            (&PyArgKind::U32, &PyArg::U32(_)) => true,
+            (&PyArgKind::U16, &PyArg::U16(_)) => true,
+            (&PyArgKind::U8, &PyArg::U8(_)) => true,
+            (&PyArgKind::F32, &PyArg::F32(_)) => true,
+            (&PyArgKind::F64, &PyArg::F64(_)) => true,
+            (&PyArgKind::PyBool, &PyArg::PyBool(_)) => true,
+            (&PyArgKind::PyString, &PyArg::PyString(_)) => true,
+            (&PyArgKind::None, &PyArg::None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A runtime descriptor for the shape of a (possibly nested) `PyArg` value. `unpack_pydict!`
+/// requires the nested type to be spelled out at compile time, which doesn't work when the
+/// shape is only known at runtime (eg. while parsing an arbitrary Python structure); `PyShape`
+/// and [PyDict::convert_with](struct.PyDict.html#method.convert_with) are a dynamic counterpart
+/// to that macro, built and walked at runtime instead.
+pub enum PyShape {
+    Scalar(PyArgKind),
+    Tuple(Vec<PyShape>),
+    List(Box<PyShape>),
+    Dict(PyDictK, Box<PyShape>),
+}
+
+/// Checks `v` against `shape`, recursing into `PyTuple`/`PyList`/`PyDict` nodes, and returns the
+/// (possibly rebuilt) value if every node matches, or the first mismatch found.
+fn validate_pyarg(v: PyArg, shape: &PyShape) -> Result<PyArg, PyDictError> {
+    match *shape {
+        PyShape::Scalar(ref kind) => {
+            if kind.matches(&v) {
+                Ok(v)
+            } else {
+                Err(PyDictError::UnexpectedValueType {
+                    expected: kind.repr(),
+                    found: v.variant_name(),
+                })
+            }
+        }
+        PyShape::Tuple(ref shapes) => {
+            match v {
+                PyArg::PyTuple(rc) => {
+                    let mut tuple = Rc::try_unwrap(rc).unwrap_or_else(|rc| (*rc).clone());
+                    if tuple.len() != shapes.len() {
+                        return Err(PyDictError::UnexpectedValueType {
+                            expected: "PyTuple of the shape's arity",
+                            found: "PyTuple of a different arity",
+                        });
+                    }
+                    let mut elems = Vec::with_capacity(shapes.len());
+                    for (i, s) in shapes.iter().enumerate() {
+                        elems.push(validate_pyarg(tuple.replace_elem(i).unwrap(), s)?);
+                    }
+                    Ok(PyArg::PyTuple(Rc::new(PyTuple::from_vec(elems))))
+                }
+                other => Err(PyDictError::UnexpectedValueType {
+                    expected: "PyTuple",
+                    found: other.variant_name(),
+                }),
+            }
+        }
+        PyShape::List(ref inner) => {
+            match v {
+                PyArg::PyList(rc) => {
+                    let mut list = Rc::try_unwrap(rc).unwrap_or_else(|rc| (*rc).clone());
+                    let mut out = VecDeque::with_capacity(list.len());
+                    while let Some(next) = list.pop() {
+                        out.push_front(validate_pyarg(next, inner)?);
+                    }
+                    Ok(PyArg::PyList(Rc::new(PyList::from_iter(out))))
+                }
+                other => Err(PyDictError::UnexpectedValueType {
+                    expected: "PyList",
+                    found: other.variant_name(),
+                }),
+            }
+        }
+        PyShape::Dict(ref k_type, ref inner) => {
+            match v {
+                PyArg::PyDict(ptr) => {
+                    let new_ptr = unsafe { validate_pydict_ptr(ptr, k_type, inner)? };
+                    Ok(PyArg::PyDict(new_ptr))
+                }
+                other => Err(PyDictError::UnexpectedValueType {
+                    expected: "PyDict",
+                    found: other.variant_name(),
+                }),
+            }
+        }
+    }
+}
+
+/// Drains a type-erased `*mut size_t` dict, validates every value against `shape`, and rebuilds
+/// it, dispatching the concrete key type via `k_type` exactly as `pydict_new` does.
+unsafe fn validate_pydict_ptr(ptr: *mut size_t,
+                              k_type: &PyDictK,
+                              shape: &PyShape)
+                              -> Result<*mut size_t, PyDictError> {
+    macro_rules! _validate_dict {
+        ($kt:ty) => {{
+            let dict: PyDict<$kt, PyArg> = PyDict::from_ptr(ptr);
+            let converted = dict.convert_with(shape)?;
+            PyDict::<$kt, PyArg>::from_iter(converted).as_ptr()
+        }};
+    }
+    Ok(match *(k_type) {
+        PyDictK::I8 => _validate_dict!(i8),
+        PyDictK::I16 => _validate_dict!(i16),
+        PyDictK::I32 => _validate_dict!(i32),
+        PyDictK::I64 => _validate_dict!(i64),
+        PyDictK::U8 => _validate_dict!(u8),
+        PyDictK::U16 => _validate_dict!(u16),
+        PyDictK::U32 => _validate_dict!(u32),
+        PyDictK::U64 => _validate_dict!(u64),
+        PyDictK::PyString => _validate_dict!(PyString),
+        PyDictK::PyBool => _validate_dict!(PyBool),
+        PyDictK::F64 => _validate_dict!(PyFloatKey),
+        PyDictK::Tuple => _validate_dict!(PyTupleKey),
+    })
+}
+
+/// FFI entry point for [PyShape](enum.PyShape.html): validates and rebuilds `dict` against
+/// `shape`, dispatching the concrete key type via `k_type` exactly as `pydict_new` does.
+/// Consumes `dict`; returns a null pointer if a value doesn't match `shape`.
+#[no_mangle]
+pub unsafe extern "C" fn pydict_convert_with_shape(dict: *mut size_t,
+                                                    k_type: &PyDictK,
+                                                    shape: *mut PyShape)
+                                                    -> *mut size_t {
+    let shape = &*shape;
+    match validate_pydict_ptr(dict, k_type, shape) {
+        Ok(ptr) => ptr,
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn pyshape_scalar(kind: u32) -> *mut PyShape {
+    let kind = match kind {
+        0 => PyArgKind::I64,
+        1 => PyArgKind::I32,
+        2 => PyArgKind::I16,
+        3 => PyArgKind::I8,
+        4 => PyArgKind::U64,
+        5 => PyArgKind::U32,
+        6 => PyArgKind::U16,
+        7 => PyArgKind::U8,
+        8 => PyArgKind::F32,
+        9 => PyArgKind::F64,
+        10 => PyArgKind::PyBool,
+        11 => PyArgKind::PyString,
+        12 => PyArgKind::None,
+        _ => _rustypy_abort_xtract_fail!("type not supported in a PyShape"),
+    };
+    Box::into_raw(Box::new(PyShape::Scalar(kind)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyshape_list(inner: *mut PyShape) -> *mut PyShape {
+    let inner = Box::from_raw(inner);
+    Box::into_raw(Box::new(PyShape::List(inner)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyshape_dict(k_type: &PyDictK, inner: *mut PyShape) -> *mut PyShape {
+    let inner = Box::from_raw(inner);
+    Box::into_raw(Box::new(PyShape::Dict(*k_type, inner)))
+}
+
+/// Opaque builder for a [PyShape::Tuple](enum.PyShape.html) — since a tuple shape's arity isn't
+/// known up front, its elements are accumulated one at a time via
+/// [pyshape_tuple_push](fn.pyshape_tuple_push.html) instead of being passed in a single call.
+#[no_mangle]
+pub extern "C" fn pyshape_tuple_new() -> *mut Vec<PyShape> {
+    Box::into_raw(Box::new(Vec::new()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyshape_tuple_push(builder: *mut Vec<PyShape>, shape: *mut PyShape) {
+    let shape = *(Box::from_raw(shape));
+    (&mut *builder).push(shape);
+}
+
+/// Consumes a [pyshape_tuple_new](fn.pyshape_tuple_new.html) builder and returns the finished
+/// `PyShape::Tuple`.
+#[no_mangle]
+pub unsafe extern "C" fn pyshape_tuple_build(builder: *mut Vec<PyShape>) -> *mut PyShape {
+    let shapes = *(Box::from_raw(builder));
+    Box::into_raw(Box::new(PyShape::Tuple(shapes)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyshape_free(shape: *mut PyShape) {
+    if shape.is_null() {
+        return;
+    }
+    drop(Box::from_raw(shape));
+}
@@ -39,6 +39,8 @@ use pytypes::PyArg;
 use std::ops::{Index, IndexMut};
 use std::iter::{FromIterator, IntoIterator};
 use std::marker::PhantomData;
+use std::ptr;
+use std::slice;
 
 /// An analog of a Python list which contains an undefined number of elements of
 /// a single kind, of any [supported type](../../../rustypy/pytypes/enum.PyArg.html).
@@ -101,6 +103,13 @@ impl PyList {
             target_t: PhantomData,
         }
     }
+
+    /// Drains every element out of the list in one pass, for bulk extraction over the FFI.
+    /// Unlike repeatedly calling [PyList::remove](#method.remove) (O(n) per call, O(n²) for
+    /// the whole list), a single `drain` call is O(n) overall.
+    pub fn drain_iter(&mut self) -> ::std::vec::Drain<PyArg> {
+        self.members.drain(..)
+    }
 }
 
 impl<T> FromIterator<T> for PyList
@@ -182,7 +191,8 @@ impl<'a> IndexMut<usize> for PyList {
 /// # #[macro_use] extern crate rustypy;
 /// # fn main(){
 /// use rustypy::{PyList, PyString};
-/// let string_list = Box::new(PyList::from(vec!["Python", "in", "Rust"]));
+/// use std::rc::Rc;
+/// let string_list = Rc::new(PyList::from(vec!["Python", "in", "Rust"]));
 /// let unpacked = unpack_pylist!(string_list; PyList{PyString => PyString});
 /// # }
 /// ```
@@ -193,7 +203,8 @@ impl<'a> IndexMut<usize> for PyList {
 /// # #[macro_use] extern crate rustypy;
 /// # fn main(){
 /// use rustypy::PyList;
-/// let int_list = Box::new(PyList::from(vec![1i32; 5]));
+/// use std::rc::Rc;
+/// let int_list = Rc::new(PyList::from(vec![1i32; 5]));
 /// let unpacked = unpack_pylist!(int_list; PyList{I32 => i32});
 /// # }
 /// ```
@@ -205,16 +216,17 @@ impl<'a> IndexMut<usize> for PyList {
 /// # #[macro_use] extern crate rustypy;
 /// # fn main(){
 /// #    use rustypy::{PyList, PyArg};
+/// #    use std::rc::Rc;
 /// #    let list = PyList::from(vec![
-/// #        pytuple!(PyArg::PyList(Box::new(PyList::from(vec![
+/// #        pytuple!(PyArg::PyList(Rc::new(PyList::from(vec![
 /// #                    pytuple!(PyArg::I64(1), PyArg::I64(2), PyArg::I64(3))]))),
 /// #                 PyArg::F32(0.1)),
-/// #        pytuple!(PyArg::PyList(Box::new(PyList::from(vec![
+/// #        pytuple!(PyArg::PyList(Rc::new(PyList::from(vec![
 /// #                    pytuple!(PyArg::I64(3), PyArg::I64(2), PyArg::I64(1))]))),
 /// #                 PyArg::F32(0.2))
 /// #        ]).as_ptr();
 /// // list from Python: [([(i64; 3)], f32)]
-/// let list = unsafe { Box::new(PyList::from_ptr(list)) };
+/// let list = Rc::new(unsafe { PyList::from_ptr(list) });
 /// let unpacked = unpack_pylist!(list;
 ///     PyList{
 ///         PyTuple{(
@@ -228,7 +240,7 @@ impl<'a> IndexMut<usize> for PyList {
 #[macro_export]
 macro_rules! unpack_pylist {
     ( $pylist:ident; PyList { $o:tt { $($t:tt)* } } ) => {{
-        let mut unboxed = *($pylist);
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pylist).unwrap_or_else(|v| (*v).clone());
         use std::collections::VecDeque;
         let mut list = VecDeque::with_capacity(unboxed.len());
         for _ in 0..unboxed.len() {
@@ -244,12 +256,12 @@ macro_rules! unpack_pylist {
         Vec::from(list)
     }};
     ( $pytuple:ident; PyTuple { $t:tt } ) => {{
-        let mut unboxed = *($pytuple);
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pytuple).unwrap_or_else(|v| (*v).clone());
         unpack_pytuple!(unboxed; $t)
     }};
     ( $pylist:ident; PyList{$t:tt => $type_:ty} ) => {{
         use rustypy::PyArg;
-        let mut unboxed = *($pylist);
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pylist).unwrap_or_else(|v| (*v).clone());
         use std::collections::VecDeque;
         let mut list = VecDeque::with_capacity(unboxed.len());
         for _ in 0..unboxed.len() {
@@ -266,7 +278,7 @@ macro_rules! unpack_pylist {
     }};
     ( FROM_TUPLE: $pylist:ident; PyList{$t:tt => $type_:ty} ) => {{
         use rustypy::PyArg;
-        let mut unboxed = &mut *($pylist);
+        let mut unboxed = ::std::rc::Rc::make_mut($pylist);
         use std::collections::VecDeque;
         let mut list = VecDeque::with_capacity(unboxed.len());
         for _ in 0..unboxed.len() {
@@ -279,7 +291,7 @@ macro_rules! unpack_pylist {
         Vec::from(list)
     }};
     ( FROM_TUPLE: $pylist:ident; PyList { $o:tt { $($t:tt)* } } ) => {{
-        let mut unboxed = &mut *($pylist);
+        let mut unboxed = ::std::rc::Rc::make_mut($pylist);
         use std::collections::VecDeque;
         let mut list = VecDeque::with_capacity(unboxed.len());
         for _ in 0..unboxed.len() {
@@ -327,3 +339,59 @@ pub unsafe extern "C" fn pylist_get_element(ptr: *mut PyList, index: usize) -> *
     let list = &mut *ptr;
     Box::into_raw(Box::new(PyList::remove(list, index)))
 }
+
+/// O(1) counterpart to [pylist_get_element](fn.pylist_get_element.html): pops the last element
+/// instead of removing at an arbitrary index, so a Python-side reverse drain (pop until a null
+/// pointer comes back) extracts a whole list in linear time instead of quadratic. Returns a
+/// null pointer once the list is empty.
+#[no_mangle]
+pub unsafe extern "C" fn pylist_pop_back(ptr: *mut PyList) -> *mut PyArg {
+    let list = &mut *ptr;
+    match list.pop() {
+        Some(val) => Box::into_raw(Box::new(val)),
+        None => {
+            let p: *const PyArg = ptr::null();
+            p as *mut PyArg
+        }
+    }
+}
+
+/// Typed, allocation-light fast paths for a `PyList` of a single scalar `PyArg` variant: a
+/// constructor that moves a contiguous `$ty` buffer in one call instead of one `pylist_push`
+/// per element, and an extractor that copies the list's elements into a caller-owned buffer in
+/// one call instead of one `pylist_get_element` per element. This is the hot path for large
+/// homogeneous lists of primitives, where per-element boxing otherwise dominates conversion
+/// time.
+macro_rules! scalar_fastpath {
+    ($ty:ty, $variant:ident, $from_fn:ident, $into_fn:ident) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn $from_fn(ptr: *const $ty, len: usize) -> *mut PyList {
+            let src = slice::from_raw_parts(ptr, len);
+            let members = src.iter().map(|v| PyArg::$variant(*v)).collect();
+            PyList { members: members }.as_ptr()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn $into_fn(list: &PyList, out: *mut $ty, len: usize) {
+            let out = slice::from_raw_parts_mut(out, len);
+            for (slot, member) in out.iter_mut().zip(list.members.iter()) {
+                match *member {
+                    PyArg::$variant(v) => *slot = v,
+                    _ => _rustypy_abort_xtract_fail!(
+                        "expected a homogeneous PyList of a single scalar type"),
+                }
+            }
+        }
+    };
+}
+
+scalar_fastpath!(i64, I64, pylist_from_i64_slice, pylist_copy_into_i64_slice);
+scalar_fastpath!(i32, I32, pylist_from_i32_slice, pylist_copy_into_i32_slice);
+scalar_fastpath!(i16, I16, pylist_from_i16_slice, pylist_copy_into_i16_slice);
+scalar_fastpath!(i8, I8, pylist_from_i8_slice, pylist_copy_into_i8_slice);
+scalar_fastpath!(u64, U64, pylist_from_u64_slice, pylist_copy_into_u64_slice);
+scalar_fastpath!(u32, U32, pylist_from_u32_slice, pylist_copy_into_u32_slice);
+scalar_fastpath!(u16, U16, pylist_from_u16_slice, pylist_copy_into_u16_slice);
+scalar_fastpath!(u8, U8, pylist_from_u8_slice, pylist_copy_into_u8_slice);
+scalar_fastpath!(f32, F32, pylist_from_f32_slice, pylist_copy_into_f32_slice);
+scalar_fastpath!(f64, F64, pylist_from_f64_slice, pylist_copy_into_f64_slice);
@@ -0,0 +1,321 @@
+//! An analog of a Python `set`/`frozenset` which contains an undefined number of elements of
+//! a single type, enforcing the same single-inner-type discipline [PyList](../pylist/index.html)
+//! uses.
+//!
+//! `PySet` can be constructed from other iterable types as long as the inner type is supported;
+//! duplicate elements (by `PartialEq`) are silently dropped on insertion, exactly as a Python
+//! `set` would.
+//!
+//! ```
+//! # use rustypy::PySet;
+//! # use std::iter::FromIterator;
+//! PySet::from_iter(vec![1u32, 1, 2]); // collapses to {1, 2}
+//! PySet::from(vec![1u32; 3]); // moved, collapses to {1}
+//! ```
+//!
+//! `PyFrozenSet` is the immutable counterpart: build a `PySet`, then freeze it with
+//! `PyFrozenSet::from`. It offers the same read-only surface (`contains`, `len`, `into_iter`)
+//! but no way to mutate it afterwards.
+//!
+//! ## Unpacking PySet from Python
+//! Is recommended to use the [unpack_pyset!](../../macro.unpack_pyset!.html) macro in order
+//! to convert a PySet to a Rust native type. Check the macro documentation for more info.
+
+use pytypes::PyArg;
+
+use std::iter::{FromIterator, IntoIterator};
+use std::marker::PhantomData;
+
+/// An analog of a Python `set` which contains an undefined number of elements of
+/// a single kind, of any [supported type](../../../rustypy/pytypes/enum.PyArg.html).
+///
+/// Read the [module docs](index.html) for more information.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PySet {
+    members: Vec<PyArg>,
+}
+
+impl PySet {
+    /// Constructs a new, empty `PySet`.
+    ///
+    /// The set will not allocate until elements are inserted into it.
+    pub fn new() -> PySet {
+        PySet { members: Vec::new() }
+    }
+
+    /// Inserts an element into the set. Returns `true` if the set did not already contain it,
+    /// `false` if it was already present (and so the set is left unchanged).
+    pub fn insert<T>(&mut self, a: T) -> bool
+        where PyArg: From<T>
+    {
+        let a = PyArg::from(a);
+        if self.members.contains(&a) {
+            false
+        } else {
+            self.members.push(a);
+            true
+        }
+    }
+
+    /// Returns `true` if the set contains an element equal to `a`.
+    pub fn contains<T>(&self, a: T) -> bool
+        where PyArg: From<T>
+    {
+        self.members.contains(&PyArg::from(a))
+    }
+
+    /// Removes an element from the set, returning `true` if it was present.
+    pub fn remove<T>(&mut self, a: T) -> bool
+        where PyArg: From<T>
+    {
+        let a = PyArg::from(a);
+        if let Some(pos) = self.members.iter().position(|e| e == &a) {
+            self.members.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the number of elements in the PySet.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Removes the last element from the set and returns it, or ```None``` if it is empty.
+    pub fn pop(&mut self) -> Option<PyArg> {
+        self.members.pop()
+    }
+
+    /// Get a PySet from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PySet) -> PySet {
+        *(Box::from_raw(ptr))
+    }
+
+    /// Return a PySet as a raw pointer.
+    pub fn as_ptr(self) -> *mut PySet {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Consume self and turn it into an iterator.
+    pub fn into_iter<T: From<PyArg>>(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.members.into_iter(),
+            target_t: PhantomData,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PySet
+    where PyArg: From<T>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut c = PySet::new();
+        for e in iter {
+            c.insert(e);
+        }
+        c
+    }
+}
+
+impl<T> From<Vec<T>> for PySet
+    where PyArg: From<T>
+{
+    fn from(v: Vec<T>) -> PySet {
+        PySet::from_iter(v)
+    }
+}
+
+pub struct IntoIter<T> {
+    target_t: PhantomData<T>,
+    inner: ::std::vec::IntoIter<PyArg>,
+}
+
+impl<T> Iterator for IntoIter<T>
+    where T: From<PyArg>
+{
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        match self.inner.next() {
+            Some(val) => Some(<T>::from(val)),
+            None => None,
+        }
+    }
+    fn collect<B>(self) -> B
+        where B: FromIterator<Self::Item>
+    {
+        self.inner.map(|x| <T>::from(x)).collect::<B>()
+    }
+}
+
+/// The immutable counterpart of [PySet](struct.PySet.html). Once frozen there is no way to
+/// insert or remove elements, only to read them back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PyFrozenSet {
+    members: Vec<PyArg>,
+}
+
+impl PyFrozenSet {
+    /// Returns `true` if the set contains an element equal to `a`.
+    pub fn contains<T>(&self, a: T) -> bool
+        where PyArg: From<T>
+    {
+        self.members.contains(&PyArg::from(a))
+    }
+
+    /// Returns the number of elements in the PyFrozenSet.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Get a PyFrozenSet from a previously boxed raw pointer.
+    pub unsafe fn from_ptr(ptr: *mut PyFrozenSet) -> PyFrozenSet {
+        *(Box::from_raw(ptr))
+    }
+
+    /// Return a PyFrozenSet as a raw pointer.
+    pub fn as_ptr(self) -> *mut PyFrozenSet {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Consume self and turn it into an iterator.
+    pub fn into_iter<T: From<PyArg>>(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.members.into_iter(),
+            target_t: PhantomData,
+        }
+    }
+}
+
+impl From<PySet> for PyFrozenSet {
+    fn from(s: PySet) -> PyFrozenSet {
+        PyFrozenSet { members: s.members }
+    }
+}
+
+/// Consumes an `Rc<PySet<PyArg(T)>>` content and returns a `Vec<T>` from it, no copies
+/// are performed in the process.
+///
+/// All inner elements are moved out if possible, if not (like with PyTuples) are copied.
+/// Follows the exact grammar of [unpack_pylist!](../rustypy/macro.unpack_pylist!.html), just
+/// for a `PySet` instead of a `PyList`.
+///
+/// # Examples
+///
+/// A simple PySet which contains PyString types::
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main(){
+/// use rustypy::{PySet, PyString};
+/// use std::rc::Rc;
+/// let string_set = Rc::new(PySet::from(vec!["Python", "in", "Rust"]));
+/// let unpacked = unpack_pyset!(string_set; PySet{PyString => PyString});
+/// # }
+/// ```
+///
+/// And an other with i32:
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main(){
+/// use rustypy::PySet;
+/// use std::rc::Rc;
+/// let int_set = Rc::new(PySet::from(vec![1i32; 5]));
+/// let unpacked = unpack_pyset!(int_set; PySet{I32 => i32});
+/// # }
+/// ```
+///
+#[macro_export]
+macro_rules! unpack_pyset {
+    ( $pyset:ident; PySet { $o:tt { $($t:tt)* } } ) => {{
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pyset).unwrap_or_else(|v| (*v).clone());
+        use std::collections::VecDeque;
+        let mut set = VecDeque::with_capacity(unboxed.len());
+        for _ in 0..unboxed.len() {
+            match unboxed.pop() {
+                Some(PyArg::$o(val)) => {
+                    let inner = unpack_pyset!(val; $o { $($t)* });
+                    set.push_front(inner);
+                },
+                Some(_) => _rustypy_abort_xtract_fail!("failed while converting pyset to vec"),
+                None => {}
+            }
+        };
+        Vec::from(set)
+    }};
+    ( $pytuple:ident; PyTuple { $t:tt } ) => {{
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pytuple).unwrap_or_else(|v| (*v).clone());
+        unpack_pytuple!(unboxed; $t)
+    }};
+    ( $pyset:ident; PySet{$t:tt => $type_:ty} ) => {{
+        use rustypy::PyArg;
+        let mut unboxed = ::std::rc::Rc::try_unwrap($pyset).unwrap_or_else(|v| (*v).clone());
+        use std::collections::VecDeque;
+        let mut set = VecDeque::with_capacity(unboxed.len());
+        for _ in 0..unboxed.len() {
+            match unboxed.pop() {
+                Some(PyArg::$t(val)) => { set.push_front(<$type_>::from(val)); },
+                Some(_) => _rustypy_abort_xtract_fail!("failed while converting pyset to vec"),
+                None => {}
+            }
+        };
+        Vec::from(set)
+    }};
+    ( $pydict:ident; PyDict{$t} ) => {{
+        unpack_pydict!( $pydict; PyDict{$t} )
+    }};
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyset_new(len: usize) -> *mut PySet {
+    let set = PySet { members: Vec::with_capacity(len) };
+    set.as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyset_push(set: &mut PySet, e: *mut PyArg) {
+    set.insert(*(Box::from_raw(e)));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyset_len(set: &mut PySet) -> usize {
+    set.len()
+}
+
+#[no_mangle]
+pub extern "C" fn pyset_free(ptr: *mut PySet) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        Box::from_raw(ptr);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyset_get_element(ptr: *mut PySet, index: usize) -> *mut PyArg {
+    let set = &mut *ptr;
+    Box::into_raw(Box::new(set.members.remove(index)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyfrozenset_from_pyset(ptr: *mut PySet) -> *mut PyFrozenSet {
+    let set = PySet::from_ptr(ptr);
+    PyFrozenSet::from(set).as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pyfrozenset_len(set: &mut PyFrozenSet) -> usize {
+    set.len()
+}
+
+#[no_mangle]
+pub extern "C" fn pyfrozenset_free(ptr: *mut PyFrozenSet) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        Box::from_raw(ptr);
+    }
+}
@@ -3,6 +3,11 @@
 //! To return to Python you must use as_ptr method and return a raw pointer.
 //! You can create them using PyString::from trait, from both &str and String.
 //!
+//! Unlike C strings, a Python `str` is free to contain interior `\0` bytes, so `PyString`
+//! stores its contents as raw UTF-8 bytes plus an explicit length rather than wrapping a
+//! `CString` — a trailing NUL is only ever added when a genuinely C-compatible pointer is
+//! requested (see [pystring_get_str](fn.pystring_get_str.html)).
+//!
 //! # Safety
 //! When passed from Python you can convert from PyString to an owned string
 //! (from\_ptr\_into\_string method) or to a &str slice (to\_str method), or
@@ -20,11 +25,12 @@
 //! // convert from raw pointer to an owned String
 //! let rust_string = PyString::from_ptr_into_string(ptr);
 //! ```
-use std::ffi::CString;
-use libc::c_char;
+use std::ffi::{CStr, CString};
+use libc::{c_char, size_t};
 
 use std::convert::From;
 use std::fmt;
+use std::slice;
 
 /// An analog of a Python String.
 ///
@@ -32,7 +38,7 @@ use std::fmt;
 #[derive(Clone)]
 #[derive(Debug)]
 pub struct PyString {
-    _inner: CString,
+    bytes: Vec<u8>,
 }
 
 impl PyString {
@@ -42,21 +48,52 @@ impl PyString {
     }
     /// Constructs an owned String from a PyString.
     pub fn to_string(&self) -> String {
-        String::from(self._inner.to_str().unwrap())
+        String::from_utf8_lossy(&self.bytes).into_owned()
     }
     /// Constructs an owned String from a raw pointer.
     pub unsafe fn from_ptr_to_string(ptr: *mut PyString) -> String {
         let pystr = *(Box::from_raw(ptr));
-        String::from(pystr._inner.to_str().unwrap())
+        String::from_utf8_lossy(&pystr.bytes).into_owned()
     }
     /// Returns PyString as a raw pointer. Use this whenever you want to return
     /// a PyString to Python.
     pub fn as_ptr(self) -> *mut PyString {
         Box::into_raw(Box::new(self))
     }
-    /// Return a PyString from a raw char pointer.
+    /// Returns the number of bytes in the underlying UTF-8 representation, not counting any
+    /// trailing NUL a C-compatible conversion might add.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    /// Return a PyString from a raw char pointer. Since this reads through `CStr`, the scan
+    /// stops at the first interior NUL byte, the same limitation a plain C string always has;
+    /// use [from_raw_parts](#method.from_raw_parts) when the caller can supply an explicit
+    /// length instead.
     pub unsafe fn from_raw(ptr: *const c_char) -> PyString {
-        PyString { _inner: CStr::from_ptr(ptr).to_owned() }
+        let bytes = CStr::from_ptr(ptr).to_bytes().to_vec();
+        PyString { bytes: bytes }
+    }
+    /// Builds a PyString from `len` bytes starting at `ptr`, with no NUL-termination
+    /// requirement and no scanning for one: arbitrary UTF-8, including interior NUL bytes,
+    /// is copied verbatim.
+    pub unsafe fn from_raw_parts(ptr: *const u8, len: usize) -> PyString {
+        let bytes = slice::from_raw_parts(ptr, len).to_vec();
+        PyString { bytes: bytes }
+    }
+    /// Encodes the string as a NUL-terminated `CString`, for the rare caller that genuinely
+    /// needs a C-compatible pointer. Any interior NUL byte truncates the result at that point,
+    /// exactly as it would for a real C string; pair this with [len](#method.len) beforehand if
+    /// the caller must detect that truncation.
+    pub fn into_cstring_lossy(self) -> CString {
+        match CString::new(self.bytes) {
+            Ok(cstr) => cstr,
+            Err(err) => {
+                let mut bytes = err.into_vec();
+                let nul_pos = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                bytes.truncate(nul_pos);
+                CString::new(bytes).unwrap()
+            }
+        }
     }
 }
 
@@ -69,14 +106,14 @@ impl fmt::Display for PyString {
 impl<'a> From<&'a str> for PyString {
     /// Copies a string slice to a PyString.
     fn from(s: &'a str) -> PyString {
-        PyString { _inner: CString::new(s).unwrap() }
+        PyString { bytes: s.as_bytes().to_vec() }
     }
 }
 
 impl From<String> for PyString {
     /// Copies a String to a PyString.
     fn from(s: String) -> PyString {
-        PyString { _inner: CString::new(s).unwrap() }
+        PyString { bytes: s.into_bytes() }
     }
 }
 
@@ -91,18 +128,37 @@ pub extern "C" fn pystring_free(ptr: *mut PyString) {
     }
 }
 
-use std::ffi::CStr;
-/// Creates a PyString wrapper from a raw c_char pointer
+/// Creates a PyString wrapper from a raw, NUL-terminated c_char pointer. Truncates at the
+/// first interior NUL byte, same as any C string; use
+/// [pystring_new_with_len](fn.pystring_new_with_len.html) to avoid that.
 #[no_mangle]
 pub extern "C" fn pystring_new(ptr: *const c_char) -> *mut PyString {
-    let pystr = PyString { _inner: unsafe { CStr::from_ptr(ptr).to_owned() } };
-    pystr.as_ptr()
+    unsafe { PyString::from_raw(ptr) }.as_ptr()
+}
+
+/// Creates a PyString wrapper from a buffer of `len` bytes, with no NUL-termination
+/// requirement: arbitrary UTF-8, including interior NUL bytes, is copied verbatim.
+#[no_mangle]
+pub unsafe extern "C" fn pystring_new_with_len(ptr: *const u8, len: size_t) -> *mut PyString {
+    PyString::from_raw_parts(ptr, len).as_ptr()
+}
+
+/// Returns the number of bytes in `ptr`'s underlying UTF-8 representation, not counting any
+/// trailing NUL a C-compatible conversion might add. Call this before
+/// [pystring_get_str](fn.pystring_get_str.html) so a string with interior NUL bytes doesn't
+/// silently look truncated.
+#[no_mangle]
+pub unsafe extern "C" fn pystring_len(ptr: &PyString) -> size_t {
+    ptr.len()
 }
 
-/// Consumes the wrapper and returns a raw c_char pointer. Afterwards is not necessary
-/// to destruct it as it has already been consumed.
+/// Consumes the wrapper and returns a raw, NUL-terminated c_char pointer. Afterwards is not
+/// necessary to destruct it as it has already been consumed. Any interior NUL byte truncates
+/// the result, exactly as it would for a real C string; call
+/// [pystring_len](fn.pystring_len.html) on the original PyString beforehand if the caller must
+/// detect that truncation instead of silently losing the tail of the string.
 #[no_mangle]
 pub extern "C" fn pystring_get_str(ptr: *mut PyString) -> *const c_char {
     let pystr: PyString = unsafe { PyString::from_ptr(ptr) };
-    pystr._inner.into_raw()
+    pystr.into_cstring_lossy().into_raw()
 }
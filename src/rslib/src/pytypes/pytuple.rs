@@ -29,24 +29,70 @@
 //! Is recommended to use the [unpack_pytuple!](../../macro.unpack_pytuple!.html) macro in order
 //! to convert a PyTuple to a Rust native type. Check the macro documentation for more info.
 
+use std::error::Error;
+use std::fmt;
 use std::iter::IntoIterator;
 use std::ops::Deref;
 use std::mem;
+use std::slice;
 
 use pytypes::PyArg;
 
+/// Error returned by the fallible extraction API (`try_as_ref`/`try_as_mut`/`try_replace_elem`
+/// and [try_unpack_pytuple!](macro.try_unpack_pytuple!.html)), carrying enough detail for a
+/// Rust caller to recover instead of the process aborting like `unpack_pytuple!` does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PyTupleExtractError {
+    /// `index` was not a valid position in a tuple of length `len`.
+    OutOfRange { index: usize, len: usize },
+    /// The element at `index` was not of the expected `PyArg` variant.
+    TypeMismatch {
+        index: usize,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for PyTupleExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PyTupleExtractError::OutOfRange { index, len } => write!(
+                f,
+                "PyTuple index {} out of range for a tuple of length {}",
+                index, len
+            ),
+            PyTupleExtractError::TypeMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "PyTuple element {}: expected {}, found {}",
+                index, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for PyTupleExtractError {}
+
 /// An analog of a Python tuple, will accept an undefined number of other
 /// [supported types](../../../rustypy/pytypes/enum.PyArg.html).
 ///
+/// Backed by a `Vec<PyArg>` so indexing (`as_ref`/`as_mut`/`replace_elem`) and `len` are O(1)
+/// instead of walking a chain of nodes.
+///
 /// Read the [module docs](index.html) for more information.
 #[derive(Clone, Debug, PartialEq)]
 pub struct PyTuple {
-    pub elem: PyArg,
-    pub idx: usize,
-    pub next: Option<Box<PyTuple>>,
+    elems: Vec<PyArg>,
 }
 
 impl<'a> PyTuple {
+    /// Builds a PyTuple directly from its elements, in order.
+    pub fn from_vec(elems: Vec<PyArg>) -> PyTuple {
+        PyTuple { elems }
+    }
     /// Get a PyTuple from a previously boxed raw pointer.
     pub unsafe fn from_ptr(ptr: *mut PyTuple) -> PyTuple {
         *(Box::from_raw(ptr))
@@ -54,47 +100,57 @@ impl<'a> PyTuple {
     /// Get a mutable reference to an inner element of the tuple, takes as argument the position
     /// of the element and returns a Result.
     pub fn as_mut(&mut self, idx: usize) -> Result<&mut PyArg, &str> {
-        if idx == self.idx {
-            Ok(&mut self.elem)
-        } else {
-            match self.next {
-                Some(ref mut e) => (**e).as_mut(idx),
-                None => Err("PyTuple index out of range."),
-            }
-        }
+        self.elems.get_mut(idx).ok_or("PyTuple index out of range.")
     }
     #[doc(hidden)]
     pub fn replace_elem(&mut self, idx: usize) -> Result<PyArg, &str> {
-        if idx == self.idx {
-            let e = mem::replace(&mut self.elem, PyArg::None);
-            Ok(e)
-        } else {
-            match self.next {
-                Some(ref mut e) => (**e).replace_elem(idx),
-                None => Err("PyTuple index out of range."),
-            }
-        }
+        let e = self.as_mut(idx)?;
+        Ok(mem::replace(e, PyArg::None))
     }
     /// Get a regular reference to an inner element of the tuple, takes as argument the position
     /// of the element and returns a Result.
     pub fn as_ref(&self, idx: usize) -> Result<&PyArg, &str> {
-        if idx == self.idx {
-            Ok(&self.elem)
-        } else {
-            match self.next {
-                Some(ref e) => (**e).as_ref(idx),
-                None => Err("PyTuple index out of range."),
-            }
-        }
+        self.elems.get(idx).ok_or("PyTuple index out of range.")
+    }
+    /// Like [as_ref](PyTuple::as_ref), but the error carries the tuple's length instead of a
+    /// plain message, so callers (ie. [try_unpack_pytuple!](macro.try_unpack_pytuple!.html))
+    /// can report it alongside a type mismatch without losing detail.
+    pub fn try_as_ref(&self, idx: usize) -> Result<&PyArg, PyTupleExtractError> {
+        let len = self.elems.len();
+        self.elems
+            .get(idx)
+            .ok_or(PyTupleExtractError::OutOfRange { index: idx, len })
+    }
+    /// Like [as_mut](PyTuple::as_mut), but returns a [PyTupleExtractError] instead.
+    pub fn try_as_mut(&mut self, idx: usize) -> Result<&mut PyArg, PyTupleExtractError> {
+        let len = self.elems.len();
+        self.elems
+            .get_mut(idx)
+            .ok_or(PyTupleExtractError::OutOfRange { index: idx, len })
+    }
+    /// Like [replace_elem](PyTuple::replace_elem), but returns a [PyTupleExtractError] instead.
+    #[doc(hidden)]
+    pub fn try_replace_elem(&mut self, idx: usize) -> Result<PyArg, PyTupleExtractError> {
+        let e = self.try_as_mut(idx)?;
+        Ok(mem::replace(e, PyArg::None))
     }
     fn push(&mut self, next: PyTuple) {
-        self.next = Some(Box::new(next));
+        self.elems.extend(next.elems);
     }
     pub fn len(&self) -> usize {
-        match self.next {
-            Some(ref e) => e.len(),
-            None => self.idx + 1,
+        self.elems.len()
+    }
+    /// Clones the elements in `[start, end)` into a fresh tuple. Both bounds are clamped to the
+    /// tuple's length rather than panicking, and a `start >= end` (after clamping) yields an
+    /// empty tuple, mirroring how slicing an empty `Vec` behaves.
+    pub fn slice(&self, start: usize, end: usize) -> PyTuple {
+        let len = self.elems.len();
+        let start = start.min(len);
+        let end = end.min(len);
+        if start >= end {
+            return PyTuple::from_vec(Vec::new());
         }
+        PyTuple::from_vec(self.elems[start..end].to_vec())
     }
     /// Returns self as raw pointer. Use this method when returning a PyTuple to Python.
     pub fn as_ptr(self) -> *mut PyTuple {
@@ -104,22 +160,20 @@ impl<'a> PyTuple {
 
 impl<'a> IntoIterator for &'a PyTuple {
     type Item = &'a PyArg;
-    type IntoIter = ::std::vec::IntoIter<&'a PyArg>;
+    type IntoIter = slice::Iter<'a, PyArg>;
     fn into_iter(self) -> Self::IntoIter {
-        let l = self.len();
-        let mut iter = Vec::with_capacity(l);
-        for i in 0..l {
-            iter.push(self.as_ref(i).unwrap());
-        }
-        iter.into_iter()
+        self.elems.iter()
     }
 }
 
 impl Deref for PyTuple {
     type Target = PyArg;
 
+    /// Derefs to the first element of the tuple. Assumes the tuple is non-empty, which holds
+    /// for every PyTuple constructed through [pytuple!](../../macro.pytuple!.html) or
+    /// `pytuple_new`; an empty tuple (such as an empty `pytuple_get_slice` result) panics here.
     fn deref(&self) -> &PyArg {
-        &self.elem
+        &self.elems[0]
     }
 }
 
@@ -140,51 +194,19 @@ impl Deref for PyTuple {
 macro_rules! pytuple {
     ( $( $elem:ident ),+ ) => {{
         use rustypy::PyTuple;
-        let mut cnt;
-        let mut tuple = Vec::new();
-        cnt = 0usize;
+        let mut elems = Vec::new();
         $(
-            let tuple_e = PyTuple {
-                elem: $elem,
-                idx: cnt,
-                next: None,
-            };
-            tuple.push(tuple_e);
-            cnt += 1;
+            elems.push($elem);
         )*;
-        if cnt == tuple.len() {}; // stub to remove warning...
-        let t_len = tuple.len() - 1;
-        for i in 1..(t_len + 1) {
-            let idx = t_len - i;
-            let last = tuple.pop().unwrap();
-            let prev = tuple.get_mut(idx).unwrap();
-            prev.next = Some(Box::new(last));
-        }
-        tuple.pop().unwrap()
+        PyTuple::from_vec(elems)
     }};
     ( $( $elem:expr ),+ ) => {{
         use rustypy::PyTuple;
-        let mut cnt;
-        let mut tuple = Vec::new();
-        cnt = 0usize;
+        let mut elems = Vec::new();
         $(
-            let tuple_e = PyTuple {
-                elem: $elem,
-                idx: cnt,
-                next: None,
-            };
-            tuple.push(tuple_e);
-            cnt += 1;
+            elems.push($elem);
         )*;
-        if cnt == 0 {}; // stub to remove warning...
-        let t_len = tuple.len() - 1;
-        for i in 1..(t_len + 1) {
-            let idx = t_len - i;
-            let last = tuple.pop().unwrap();
-            let prev = tuple.get_mut(idx).unwrap();
-            prev.next = Some(Box::new(last));
-        }
-        tuple.pop().unwrap()
+        PyTuple::from_vec(elems)
     }};
 }
 
@@ -199,6 +221,10 @@ macro_rules! pytuple {
 /// (ie. `Vec<T>`) and require valid syntax for their respective unpack macro (ie.
 /// [unpack_pytuple!](../rustypy/macro.unpack_pylist!.html)).
 ///
+/// A `{with(fun)}` spell hands the element at that position to a user-supplied
+/// `Fn(&PyArg) -> T`, for elements that need converting into a domain type (an enum, a newtype,
+/// a validated range) instead of matching directly against a `PyArg` variant.
+///
 /// # Examples
 ///
 /// Unpack a PyTuple which contains a two PyDict types with PyString keys
@@ -221,6 +247,25 @@ macro_rules! pytuple {
 /// # }
 /// ```
 ///
+/// Convert an element with a custom function instead of matching a `PyArg` variant directly:
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main(){
+/// # use rustypy::{PyTuple, PyArg};
+/// fn double_it(e: &PyArg) -> i64 {
+///     match e {
+///         &PyArg::I64(ref val) => val * 2,
+///         _ => panic!("expected an I64"),
+///     }
+/// }
+/// let mut pytuple = pytuple!(PyArg::I64(21)).as_ptr();
+/// let mut pytuple = unsafe { PyTuple::from_ptr(pytuple) };
+/// let (doubled,) = unpack_pytuple!(pytuple; ({with(double_it)},));
+/// assert_eq!(doubled, 42);
+/// # }
+/// ```
+///
 #[macro_export]
 macro_rules! unpack_pytuple {
     ($t:ident; ($($p:tt,)+) ) => {{
@@ -237,7 +282,7 @@ macro_rules! unpack_pytuple {
                 $i += 1;
                 if $i == 0 {}; // stub to remove warning...
                 let mut cnt = 0;
-                let val = *(val); // move out of box
+                let val = ::std::rc::Rc::try_unwrap(val).unwrap_or_else(|v| (*v).clone());
                 ($(
                     unpack_pytuple!(val; cnt; elem: $p)
                 ,)*)
@@ -267,6 +312,15 @@ macro_rules! unpack_pytuple {
             _ => _rustypy_abort_xtract_fail!("failed while extracting a PyList inside a PyTuple"),
         }
     }};
+    // `{with(fun)}` hands the raw element to a user function `Fn(&PyArg) -> T` instead of
+    // matching it against a fixed PyArg variant, so it can be converted into a domain type.
+    ($t:ident; $i:ident; elem: {with($f:expr)}) => {{
+        let e = $t.as_ref($i).unwrap();
+        let val = ($f)(e);
+        $i += 1;
+        if $i == 0 {}; // stub to remove warning...
+        val
+    }};
     ($t:ident; $i:ident; elem: PyBool) => {{
         let e = $t.as_ref($i).unwrap();
         match e {
@@ -390,13 +444,250 @@ macro_rules! unpack_pytuple {
     }};
 }
 
+/// Result-returning sibling of [unpack_pytuple!](macro.unpack_pytuple!.html): instead of
+/// aborting the process on a variant mismatch, yields
+/// `Result<(...), `[`PyTupleExtractError`](pytuple/struct.PyTupleExtractError.html)`>` carrying
+/// the failing index and the expected/found variant names, so a Rust caller can recover from a
+/// malformed tuple coming from dynamic Python data.
+///
+/// Supports the same scalar spells as `unpack_pytuple!` (`I64`, `PyBool`, `PyString`, etc.) plus
+/// nested `PyTuple`s. Nested `{PyList{...}}`/`{PyDict{...}}` spells still delegate to
+/// `unpack_pylist!`/`unpack_pydict!`, which abort on a mismatch (`try_unpack_pydict!` is not
+/// used here), so a container field nested inside a tuple can still abort the process.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main(){
+/// # use rustypy::{PyTuple, PyArg};
+/// let mut pytuple = pytuple!(PyArg::I64(10), PyArg::F32(10.5)).as_ptr();
+/// let mut pytuple = unsafe { PyTuple::from_ptr(pytuple) };
+/// let unpacked: Result<(i64, f32), _> = try_unpack_pytuple!(pytuple; (I64, F32,));
+/// assert_eq!(unpacked.unwrap(), (10, 10.5));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_unpack_pytuple {
+    ($t:ident; ($($p:tt,)+) ) => {{
+        use rustypy::PyArg;
+        (|| -> Result<_, $crate::pytypes::pytuple::PyTupleExtractError> {
+            let mut cnt = 0;
+            Ok(($(
+                try_unpack_pytuple!($t; cnt; elem: $p)?
+            ,)*))
+        })()
+    }};
+    ($t:ident; $i:ident; elem: ($($p:tt,)+))  => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_replace_elem(idx) {
+            Ok(PyArg::PyTuple(val)) => {
+                let mut cnt = 0;
+                let val = ::std::rc::Rc::try_unwrap(val).unwrap_or_else(|v| (*v).clone());
+                let mut val = val;
+                (|| -> Result<_, $crate::pytypes::pytuple::PyTupleExtractError> {
+                    Ok(($(
+                        try_unpack_pytuple!(val; cnt; elem: $p)?
+                    ,)*))
+                })()
+            },
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "PyTuple", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: {PyDict{$u:tt}}) => {{
+        Ok::<_, $crate::pytypes::pytuple::PyTupleExtractError>(
+            unpack_pytuple!($t; $i; elem: {PyDict{$u}}))
+    }};
+    ($t:ident; $i:ident; elem: {PyList{$($u:tt)*}}) => {{
+        Ok::<_, $crate::pytypes::pytuple::PyTupleExtractError>(
+            unpack_pytuple!($t; $i; elem: {PyList{$($u)*}}))
+    }};
+    ($t:ident; $i:ident; elem: PyBool) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::PyBool(ref val)) => Ok(val.to_bool()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "PyBool", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: PyString) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::PyString(ref val)) => Ok(val.to_string()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "PyString", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: I64) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::I64(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "I64", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: I32) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::I32(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "I32", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: I16) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::I16(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "I16", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: I8) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::I8(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "I8", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: U32) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::U32(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "U32", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: U16) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::U16(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "U16", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: U8) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::U8(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "U8", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: F32) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::F32(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "F32", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+    ($t:ident; $i:ident; elem: F64) => {{
+        let idx = $i;
+        $i += 1;
+        match $t.try_as_ref(idx) {
+            Ok(&PyArg::F64(ref val)) => Ok(val.clone()),
+            Ok(other) => Err($crate::pytypes::pytuple::PyTupleExtractError::TypeMismatch {
+                index: idx, expected: "F64", found: other.variant_name(),
+            }),
+            Err(e) => Err(e),
+        }
+    }};
+}
+
+/// Generates a `TryFrom<PyTuple>` impl for a plain struct, so it can be built straight from a
+/// PyTuple's positional elements instead of hand-writing an [unpack_pytuple!](macro.unpack_pytuple!.html)
+/// call and destructuring the resulting Rust tuple yourself.
+///
+/// Each field is matched against a spell in the same grammar `unpack_pytuple!` accepts (`I64`,
+/// `PyString`, `{PyList{...}}`, `{PyDict{...}}`, a nested `(...)` tuple, etc.), so nested
+/// containers unpack exactly as they would through `unpack_pytuple!`. A PyTuple whose length
+/// doesn't match the struct's field count returns `Err` instead of panicking; a type mismatch
+/// on a given field still aborts the process, since each field goes through `unpack_pytuple!`
+/// rather than its fallible sibling `try_unpack_pytuple!`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate rustypy;
+/// # fn main() {
+/// use std::convert::TryFrom;
+/// use rustypy::{PyArg, PyString, PyTuple};
+///
+/// struct RustyTuple {
+///     name: String,
+///     score: F64,
+/// }
+/// # type F64 = f64;
+/// impl_from_pytuple!(RustyTuple { name: PyString, score: F64 });
+///
+/// let tuple = pytuple!(PyArg::PyString(PyString::from("rusty")), PyArg::F64(9.5));
+/// let r = RustyTuple::try_from(tuple).unwrap();
+/// assert_eq!(r.name, "rusty");
+/// assert_eq!(r.score, 9.5);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! impl_from_pytuple {
+    ($struct_name:ident { $( $field:ident : $p:tt ),+ $(,)* }) => {
+        impl ::std::convert::TryFrom<$crate::PyTuple> for $struct_name {
+            type Error = String;
+            fn try_from(mut t: $crate::PyTuple) -> Result<Self, Self::Error> {
+                let expected: usize = 0 $( + { let _ = stringify!($field); 1 } )*;
+                if t.len() != expected {
+                    return Err(format!(
+                        "arity mismatch unpacking into `{}`: expected {} elements, found {}",
+                        stringify!($struct_name), expected, t.len()
+                    ));
+                }
+                let mut i = 0usize;
+                $( let $field = unpack_pytuple!(t; i; elem: $p); )*
+                Ok($struct_name { $( $field ),* })
+            }
+        }
+    };
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn pytuple_new(idx: usize, elem: *mut PyArg) -> *mut PyTuple {
-    let tuple = PyTuple {
-        elem: *(Box::from_raw(elem)),
-        idx: idx,
-        next: None,
-    };
+    // `idx` is kept for ABI compatibility; a single-element tuple's only slot is always 0,
+    // position within the final tuple is now tracked by Vec order via `pytuple_push`.
+    let _ = idx;
+    let tuple = PyTuple::from_vec(vec![*(Box::from_raw(elem))]);
     tuple.as_ptr()
 }
 
@@ -429,3 +720,15 @@ pub unsafe extern "C" fn pytuple_get_element(ptr: *mut PyTuple, index: usize) ->
     let copied: PyArg = (*elem).clone();
     Box::into_raw(Box::new(copied))
 }
+
+/// Clones a contiguous sub-tuple of `[start, end)` elements, so Python-side wrappers can
+/// obtain a slice without popping elements off one at a time over the FFI.
+#[no_mangle]
+pub unsafe extern "C" fn pytuple_get_slice(
+    ptr: *mut PyTuple,
+    start: usize,
+    end: usize,
+) -> *mut PyTuple {
+    let tuple = &*ptr;
+    tuple.slice(start, end).as_ptr()
+}